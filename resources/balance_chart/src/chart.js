@@ -7,6 +7,7 @@ import {
   PointElement,
   LineElement,
   Decimation,
+  Title,
 } from "chart.js";
 Chart.register(
   TimeScale,
@@ -16,8 +17,12 @@ Chart.register(
   PointElement,
   LineElement,
   Decimation,
+  Title,
 );
 import "chartjs-adapter-moment";
+import moment from "moment";
+import "moment/locale/nl";
+import "moment/locale/de";
 
 const colors = {
   purple: {
@@ -38,10 +43,30 @@ const colors = {
     "#0099CC", // teal
   ],
 };
+const themes = {
+  dark: {
+    text: "#ddd",
+    grid: "rgba(255, 255, 255, 0.1)",
+    shadow: "#e15bff",
+  },
+  light: {
+    text: "#333",
+    grid: "rgba(0, 0, 0, 0.1)",
+    shadow: "rgba(0, 0, 0, 0.25)",
+  },
+};
 const botDataStr = decodeURIComponent(window.location.hash.substring(1));
 const botData = JSON.parse(botDataStr);
-const ctx = document.querySelector(".chart canvas").getContext("2d");
-const gradient = ctx.createLinearGradient(0, 25, 0, 300);
+const chartOptions = botData.options;
+const locale = botData.locale;
+moment.locale(locale.code);
+const theme = themes[chartOptions.theme] || themes.dark;
+const chartElement = document.querySelector(".chart");
+chartElement.classList.add(chartOptions.theme);
+chartElement.style.width = `${chartOptions.width}px`;
+chartElement.style.height = `${chartOptions.height}px`;
+const ctx = chartElement.querySelector("canvas").getContext("2d");
+const gradient = ctx.createLinearGradient(0, 25, 0, chartOptions.height);
 gradient.addColorStop(0, colors.purple.half);
 gradient.addColorStop(0.35, colors.purple.quarter);
 gradient.addColorStop(1, colors.purple.zero);
@@ -65,7 +90,29 @@ LineController.prototype.draw = function () {
   draw.apply(this, arguments);
   ctx.stroke = _stroke;
 };
-Chart.defaults.color = "#ddd";
+Chart.defaults.color = theme.text;
+
+const abbreviateFrom = parseFloat(locale.abbreviateFrom);
+const numberFormat = new Intl.NumberFormat(locale.code, {
+  maximumFractionDigits: locale.decimals,
+});
+const shortFormat = new Intl.NumberFormat(locale.code, {
+  maximumFractionDigits: 1,
+});
+// Same suffixes as the bot's messages, see `utils/number_format.rs`
+const suffixes = [
+  [1e12, "T"],
+  [1e9, "B"],
+  [1e6, "M"],
+  [1e3, "k"],
+];
+const formatBalance = (value) => {
+  const suffix = suffixes.find(([unit]) => Math.abs(value) >= unit);
+  if (abbreviateFrom > 0 && Math.abs(value) >= abbreviateFrom && suffix) {
+    return `${shortFormat.format(value / suffix[0])}${suffix[1]}`;
+  }
+  return numberFormat.format(value);
+};
 
 const options = {
   type: "line",
@@ -73,7 +120,7 @@ const options = {
     datasets: Object.keys(botData.chartData).map((accountName, index) => {
       const color = colors.lines[index % colors.lines.length];
       return {
-        label: `${accountName} balance (USD)`,
+        label: `${accountName} (${chartOptions.currencyLabel})`,
         backgroundColor: gradient,
         pointRadius: 0,
         borderColor: color,
@@ -91,13 +138,19 @@ const options = {
   options: {
     animation: false,
     parsing: false,
-    shadowColor: "#e15bff",
+    locale: locale.code,
+    devicePixelRatio: chartOptions.deviceScaleFactor,
+    shadowColor: theme.shadow,
     responsive: true,
     maintainAspectRatio: false,
     layout: {
       padding: 10,
     },
     plugins: {
+      title: {
+        display: !!chartOptions.title,
+        text: chartOptions.title,
+      },
       decimation: {
         enabled: true,
         algorithm: "lttb",
@@ -107,6 +160,9 @@ const options = {
     scales: {
       x: {
         type: "time",
+        grid: {
+          color: theme.grid,
+        },
         ticks: {
           source: "auto",
           // Disabled rotation for performance
@@ -115,13 +171,19 @@ const options = {
         },
         title: {
           display: true,
-          text: "Time",
+          text: locale.time,
         },
       },
       y: {
+        grid: {
+          color: theme.grid,
+        },
+        ticks: {
+          callback: formatBalance,
+        },
         title: {
           display: true,
-          text: "Balance",
+          text: `${locale.balance} (${chartOptions.currencyLabel})`,
         },
       },
     },
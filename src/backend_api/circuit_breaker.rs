@@ -0,0 +1,47 @@
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<u64>,
+}
+
+/// Stops requests for a while after too many failures in a row. Once the cooldown
+/// is over requests go through again, and the first failure opens it again.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown_seconds: u64,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown_seconds: u64) -> CircuitBreaker {
+        CircuitBreaker {
+            failure_threshold,
+            cooldown_seconds,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// `Err` with the end of the cooldown while requests are paused
+    pub fn check(&self, now: u64) -> Result<(), u64> {
+        match self.state.lock().unwrap().open_until {
+            Some(until) if now < until => Err(until),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures = 0;
+        state.open_until = None;
+    }
+
+    pub fn record_failure(&self, now: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.failure_threshold {
+            state.open_until = Some(now + self.cooldown_seconds);
+        }
+    }
+}
@@ -1,99 +1,316 @@
-use std::env;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
 
-use anyhow::anyhow;
 use anyhow::Result;
+use log::debug;
+use reqwest::header::HeaderMap;
 use reqwest::Client;
-use reqwest::RequestBuilder;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
 use url::Url;
 
-use super::objects::Account;
-use super::objects::{ActiveBotsResponse, Trade, TradesResponse};
+use super::circuit_breaker::CircuitBreaker;
+use super::credentials::redact_url;
+use super::error::BackendError;
+use super::objects::{
+    Account, AccountStateSnapshot, ActiveBotsResponse, BotStatus, BotStatusResponse,
+    ControllerConfig, PortfolioHistoryFilter, PortfolioHistoryResponse, StartBotRequest, Trade,
+    TradesResponse,
+};
+use crate::config::BackendRequests;
+use crate::structs::health::HealthMonitor;
+use crate::utils::unix_timestamp::unix_timestamp;
 
 pub struct BackendAPIClient {
     base_url: Url,
     client: Client,
+    options: BackendRequests,
+    breaker: CircuitBreaker,
+    health: HealthMonitor,
 }
 
-fn add_basic_auth(rb: RequestBuilder) -> RequestBuilder {
-    if let (Ok(username), Ok(password)) = (
-        env::var("BACKEND_API_USERNAME"),
-        env::var("BACKEND_API_PASSWORD"),
-    ) {
-        rb.basic_auth(username, Some(password))
-    } else {
-        rb
+/// Fields the backend uses to report an error in an otherwise successful response
+#[derive(Deserialize)]
+struct StatusEnvelope {
+    status: Option<serde_json::Value>,
+    #[serde(alias = "message", alias = "detail")]
+    msg: Option<serde_json::Value>,
+}
+
+impl StatusEnvelope {
+    /// The reported status and message when it isn't a success
+    fn error(&self) -> Option<(String, String)> {
+        let status = self.status.as_ref()?.as_str()?;
+        if status.eq_ignore_ascii_case("success") || status.eq_ignore_ascii_case("ok") {
+            return None;
+        }
+        let message = match &self.msg {
+            Some(serde_json::Value::String(msg)) => msg.clone(),
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+        Some((status.to_string(), message))
     }
 }
 
+/// Wait before retry number `attempt` (from 0): the retry delay doubled per attempt up
+/// to the maximum, of which the upper half is random by `jitter` between 0 and 1
+pub fn retry_delay(options: &BackendRequests, attempt: u32, jitter: f64) -> Duration {
+    let delay = options
+        .retry_delay_ms
+        .saturating_mul(2u64.saturating_pow(attempt))
+        .min(options.max_retry_delay_ms);
+    let half = delay / 2;
+    Duration::from_millis(half + (half as f64 * jitter.clamp(0.0, 1.0)) as u64)
+}
+
+/// Random number between 0 and 1, good enough to spread retries
+fn jitter() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random % 1_000_000) as f64 / 1_000_000.0
+}
+
 impl BackendAPIClient {
-    pub fn new(base_url: Url) -> BackendAPIClient {
-        BackendAPIClient {
+    /// `headers` carry the credentials, see `credentials::backend_headers`
+    pub fn new(
+        base_url: Url,
+        options: BackendRequests,
+        headers: HeaderMap,
+    ) -> Result<BackendAPIClient> {
+        let client = Client::builder()
+            .default_headers(headers)
+            .timeout(Duration::from_secs(options.timeout_seconds))
+            .connect_timeout(Duration::from_secs(options.connect_timeout_seconds))
+            .build()?;
+        Ok(BackendAPIClient {
             base_url,
-            client: Client::new(),
+            client,
+            breaker: CircuitBreaker::new(
+                options.circuit_breaker_failures,
+                options.circuit_breaker_cooldown_seconds,
+            ),
+            options,
+            health: HealthMonitor::default(),
+        })
+    }
+
+    /// Results of the requests made so far, per endpoint
+    pub fn health(&self) -> &HealthMonitor {
+        &self.health
+    }
+
+    /// Makes one request to find out whether the backend accepts the credentials
+    pub async fn check_credentials(&self) -> Result<(), BackendError> {
+        self.fetch::<serde_json::Value>("get-active-bots-status", None)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn get_bots(&self) -> Result<ActiveBotsResponse, BackendError> {
+        self.get("get-active-bots-status", "get-active-bots-status")
+            .await
+    }
+
+    pub async fn get_account_state(&self) -> Result<Account, BackendError> {
+        self.get("accounts-state", "accounts-state").await
+    }
+
+    pub async fn get_trades(&self, bot_name: &str) -> Result<Vec<Trade>, BackendError> {
+        let path = format!("get-bot-history/{}", bot_name);
+        let response: TradesResponse = self.get("get-bot-history", &path).await?;
+        let inner = response.response;
+        if !(200..300).contains(&inner.status) {
+            return Err(BackendError::Backend {
+                url: redact_url(&self.base_url.join(&path).unwrap()),
+                status: inner.status.to_string(),
+                message: inner.msg,
+            });
         }
+        Ok(inner.trades)
     }
 
-    pub async fn get_bots(&self) -> Result<ActiveBotsResponse> {
-        let url = self.base_url.join("get-active-bots-status").unwrap();
-        let resp = add_basic_auth(self.client.get(url.as_str()))
-            .send()
-            .await?
-            .text()
-            .await?;
-        match serde_json::from_str::<ActiveBotsResponse>(&resp) {
-            Ok(json) => Ok(json),
-            Err(e) => Err(anyhow!(
-                "Failed parsing response for {}. Body: {}\nError: {}",
-                url,
-                resp,
-                e
-            )),
+    #[allow(dead_code)]
+    pub async fn get_bot_status(&self, bot_name: &str) -> Result<BotStatus, BackendError> {
+        let path = format!("get-bot-status/{}", bot_name);
+        let response: BotStatusResponse = self.get("get-bot-status", &path).await?;
+        Ok(response.data)
+    }
+
+    /// Every saved state of the accounts, oldest first
+    #[allow(dead_code)]
+    pub async fn get_account_state_history(
+        &self,
+    ) -> Result<Vec<AccountStateSnapshot>, BackendError> {
+        self.get("account-state-history", "account-state-history")
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn get_available_connectors(&self) -> Result<Vec<String>, BackendError> {
+        self.get("available-connectors", "available-connectors")
+            .await
+    }
+
+    #[allow(dead_code)]
+    pub async fn get_controller_configs(&self) -> Result<Vec<ControllerConfig>, BackendError> {
+        self.get("all-controller-configs", "all-controller-configs")
+            .await
+    }
+
+    /// One page of the portfolio history, pass `next_cursor` as `cursor` for the next
+    #[allow(dead_code)]
+    pub async fn get_portfolio_history(
+        &self,
+        filter: &PortfolioHistoryFilter,
+    ) -> Result<PortfolioHistoryResponse, BackendError> {
+        let body = serde_json::to_value(filter).expect("filter serializes to JSON");
+        self.request("portfolio/history", "portfolio/history", Some(&body))
+            .await
+    }
+
+    /// Starts a bot from a script or script config
+    pub async fn start_bot(&self, request: &StartBotRequest) -> Result<(), BackendError> {
+        let body = serde_json::to_value(request).expect("request serializes to JSON");
+        self.command("start-bot", &body).await
+    }
+
+    pub async fn stop_bot(
+        &self,
+        bot_name: &str,
+        skip_order_cancellation: bool,
+    ) -> Result<(), BackendError> {
+        let body = json!({
+            "bot_name": bot_name,
+            "skip_order_cancellation": skip_order_cancellation,
+            "async_backend": false,
+        });
+        self.command("stop-bot", &body).await
+    }
+
+    /// Stops one controller of a running bot through its kill switch
+    pub async fn stop_controller(
+        &self,
+        bot_name: &str,
+        controller: &str,
+    ) -> Result<(), BackendError> {
+        let path = format!("update-controller-config/{}/{}", bot_name, controller);
+        self.command(&path, &json!({ "manual_kill_switch": true }))
+            .await
+    }
+
+    /// POSTs a command once, a retry could repeat what already happened and a
+    /// rejected command says nothing about the health of the backend
+    async fn command(&self, path: &str, body: &serde_json::Value) -> Result<(), BackendError> {
+        if let Err(until) = self.breaker.check(unix_timestamp()) {
+            return Err(BackendError::CircuitOpen { until });
+        }
+        let result = self.fetch::<serde_json::Value>(path, Some(body)).await;
+        match &result {
+            Ok(_) => self.breaker.record_success(),
+            Err(e) if e.is_transient() => self.breaker.record_failure(unix_timestamp()),
+            Err(_) => {}
         }
+        result.map(|_| ())
     }
 
-    pub async fn get_latest_trade(&self, bot_name: &str) -> Result<Option<Trade>> {
-        let trades = self.get_trades(bot_name).await?;
-        return Ok(trades.last().cloned());
+    /// GETs `path` and records the result under `endpoint`, which leaves out
+    /// parameters like the bot name
+    async fn get<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        path: &str,
+    ) -> Result<T, BackendError> {
+        self.request(endpoint, path, None).await
     }
 
-    pub async fn get_account_state(&self) -> Result<Account> {
-        let url = self.base_url.join("accounts-state").unwrap();
-        let resp = add_basic_auth(self.client.get(url.as_str()))
-            .send()
-            .await?
-            .text()
-            .await?;
-
-        match serde_json::from_str::<Account>(&resp) {
-            Ok(json) => Ok(json),
-            Err(e) => Err(anyhow!(
-                "Failed parsing response for {}. Body: {}\nError: {}",
-                url,
-                resp,
-                e
-            )),
+    /// Like `get`, but POSTs `body` when there is one
+    async fn request<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T, BackendError> {
+        if let Err(until) = self.breaker.check(unix_timestamp()) {
+            // Not a new failure of the endpoint, its last real error stays shown
+            return Err(BackendError::CircuitOpen { until });
+        }
+        let result = self.fetch_with_retries(path, body).await;
+        let now = unix_timestamp();
+        match &result {
+            Ok(_) => {
+                self.breaker.record_success();
+                self.health.record_success(endpoint, now);
+            }
+            // Only an unreachable or failing backend should pause all requests or count as
+            // an outage, other errors, e.g. a bot without history, mean it answered
+            Err(e) if e.is_transient() => {
+                self.breaker.record_failure(now);
+                self.health.record_failure(endpoint, e.to_string(), now);
+            }
+            Err(_) => self.health.record_success(endpoint, now),
         }
+        result
     }
 
-    pub async fn get_trades(&self, bot_name: &str) -> Result<Vec<Trade>> {
-        let url = self
-            .base_url
-            .join(&format!("get-bot-history/{}", bot_name))
-            .unwrap();
-        let resp = add_basic_auth(self.client.get(url.as_str()))
+    async fn fetch_with_retries<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T, BackendError> {
+        let mut attempt = 0;
+        loop {
+            match self.fetch(path, body).await {
+                Err(e) if e.is_transient() && attempt < self.options.max_retries => {
+                    let delay = retry_delay(&self.options, attempt, jitter());
+                    debug!("Retrying {} in {:?} after: {}", path, delay, e);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn fetch<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T, BackendError> {
+        let url = self.base_url.join(path).unwrap();
+        // Errors only ever show the url without its password
+        let shown_url = redact_url(&url);
+        let request = match body {
+            Some(body) => self.client.post(url).json(body),
+            None => self.client.get(url),
+        };
+        let response = request
             .send()
-            .await?
+            .await
+            .map_err(|e| BackendError::from_reqwest(&shown_url, e))?;
+        let status = response.status();
+        let resp = response
             .text()
-            .await?;
-
-        match serde_json::from_str::<TradesResponse>(&resp) {
-            Ok(json) => Ok(json.response.trades),
-            Err(e) => Err(anyhow!(
-                "Failed parsing response for {}. Body: {}\nError: {}",
-                url,
-                resp,
-                e
-            )),
+            .await
+            .map_err(|e| BackendError::from_reqwest(&shown_url, e))?;
+        if !status.is_success() {
+            return Err(BackendError::from_status(
+                &shown_url,
+                status.as_u16(),
+                &resp,
+            ));
+        }
+
+        if let Ok(envelope) = serde_json::from_str::<StatusEnvelope>(&resp) {
+            if let Some((status, message)) = envelope.error() {
+                return Err(BackendError::Backend {
+                    url: shown_url,
+                    status,
+                    message,
+                });
+            }
         }
+        serde_json::from_str::<T>(&resp).map_err(|e| BackendError::decode(&shown_url, &resp, e))
     }
 }
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::env;
+
+use anyhow::{anyhow, Result};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use url::Url;
+
+use crate::config::{read_secret_file, BackendAuth, Secret};
+
+/// Value of environment variable `name`, or the contents of the file named by
+/// `name_FILE` as Docker secrets are passed
+fn env_secret(name: &str) -> Result<Option<String>> {
+    if let Ok(value) = env::var(name) {
+        return Ok(Some(value));
+    }
+    match env::var(format!("{}_FILE", name)) {
+        Ok(path) => Ok(Some(read_secret_file(path.as_ref())?)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Login from the config, or from the environment like before it could be configured
+fn resolve_auth(auth: Option<&BackendAuth>) -> Result<BackendAuth> {
+    if let Some(auth) = auth {
+        return Ok(auth.clone());
+    }
+    match (
+        env_secret("BACKEND_API_USERNAME")?,
+        env_secret("BACKEND_API_PASSWORD")?,
+    ) {
+        (Some(username), Some(password)) => Ok(BackendAuth::Basic {
+            username,
+            password: Secret::Value(password),
+        }),
+        (None, None) => Ok(BackendAuth::None),
+        _ => Err(anyhow!(
+            "Set both BACKEND_API_USERNAME and BACKEND_API_PASSWORD, or neither"
+        )),
+    }
+}
+
+fn sensitive(value: &str) -> Result<HeaderValue> {
+    let mut value = HeaderValue::from_str(value)
+        .map_err(|_| anyhow!("Backend credentials contain characters not allowed in a header"))?;
+    // Keeps reqwest from showing the value in debug output
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// Headers sent with every backend request, with the secrets read once
+pub fn backend_headers(
+    auth: Option<&BackendAuth>,
+    headers: &HashMap<String, Secret>,
+) -> Result<HeaderMap> {
+    let mut map = HeaderMap::new();
+    match resolve_auth(auth)? {
+        BackendAuth::None => {}
+        BackendAuth::Basic { username, password } => {
+            let encoded = BASE64_STANDARD.encode(format!("{}:{}", username, password.reveal()?));
+            map.insert(AUTHORIZATION, sensitive(&format!("Basic {}", encoded))?);
+        }
+        BackendAuth::Bearer { token } => {
+            map.insert(
+                AUTHORIZATION,
+                sensitive(&format!("Bearer {}", token.reveal()?))?,
+            );
+        }
+    }
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| anyhow!("Invalid backend header name \"{}\"", name))?;
+        map.insert(name, sensitive(&value.reveal()?)?);
+    }
+    Ok(map)
+}
+
+/// `url` without a username and password, for logs and messages
+pub fn redact_url(url: &Url) -> String {
+    let mut url = url.clone();
+    if url.password().is_some() {
+        let _ = url.set_password(Some("redacted"));
+    }
+    url.to_string()
+}
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use crate::utils::truncate::truncate;
+
+/// Characters of a response body kept in an error
+const BODY_PREVIEW_LENGTH: usize = 500;
+
+/// Why a request to the backend API failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackendError {
+    /// No response within the configured timeout
+    Timeout { url: String },
+    /// The backend couldn't be reached, e.g. a refused connection or DNS failure
+    Network { url: String, message: String },
+    /// 401 or 403, the credentials are missing or wrong
+    Auth { url: String, status: u16 },
+    /// 5xx or 429, worth trying again later
+    Server {
+        url: String,
+        status: u16,
+        body: String,
+    },
+    /// Any other unsuccessful status, trying again won't help
+    Http {
+        url: String,
+        status: u16,
+        body: String,
+    },
+    /// The response isn't the JSON we expect, with the start of the body
+    Decode {
+        url: String,
+        body: String,
+        message: String,
+    },
+    /// The request went through but the backend reported an error in its `status` field
+    Backend {
+        url: String,
+        status: String,
+        message: String,
+    },
+    /// Too many requests failed in a row, no request was made until `until`
+    CircuitOpen { until: u64 },
+}
+
+impl BackendError {
+    pub fn from_status(url: &str, status: u16, body: &str) -> BackendError {
+        let url = url.to_string();
+        let body = truncate(body, BODY_PREVIEW_LENGTH);
+        match status {
+            401 | 403 => BackendError::Auth { url, status },
+            429 | 500..=599 => BackendError::Server { url, status, body },
+            _ => BackendError::Http { url, status, body },
+        }
+    }
+
+    pub fn decode(url: &str, body: &str, error: serde_json::Error) -> BackendError {
+        BackendError::Decode {
+            url: url.to_string(),
+            body: truncate(body, BODY_PREVIEW_LENGTH),
+            message: error.to_string(),
+        }
+    }
+
+    pub fn from_reqwest(url: &str, error: reqwest::Error) -> BackendError {
+        if error.is_timeout() {
+            BackendError::Timeout {
+                url: url.to_string(),
+            }
+        } else {
+            // reqwest keeps the cause, like a refused connection, in the source chain
+            let error = error.without_url();
+            let mut message = error.to_string();
+            let mut source = std::error::Error::source(&error);
+            while let Some(cause) = source {
+                let cause_message = cause.to_string();
+                if !message.ends_with(&cause_message) {
+                    message.push_str(&format!(": {}", cause_message));
+                }
+                source = cause.source();
+            }
+            BackendError::Network {
+                url: url.to_string(),
+                message,
+            }
+        }
+    }
+
+    /// Whether the same request may succeed when retried
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            BackendError::Timeout { .. }
+                | BackendError::Network { .. }
+                | BackendError::Server { .. }
+        )
+    }
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendError::Timeout { url } => write!(f, "Request to {} timed out", url),
+            BackendError::Network { url, message } => {
+                write!(f, "Request to {} failed: {}", url, message)
+            }
+            BackendError::Auth { url, status } => write!(
+                f,
+                "Backend rejected the credentials for {} with status {}",
+                url, status
+            ),
+            BackendError::Server { url, status, body } => {
+                write!(f, "Server error {} for {}. Body: {}", status, url, body)
+            }
+            BackendError::Http { url, status, body } => {
+                write!(
+                    f,
+                    "Unexpected status {} for {}. Body: {}",
+                    status, url, body
+                )
+            }
+            BackendError::Decode { url, body, message } => write!(
+                f,
+                "Failed parsing response for {}. Body: {}\nError: {}",
+                url, body, message
+            ),
+            BackendError::Backend {
+                url,
+                status,
+                message,
+            } => write!(
+                f,
+                "Backend reported \"{}\" for {}: {}",
+                status, url, message
+            ),
+            BackendError::CircuitOpen { until } => write!(
+                f,
+                "Backend requests are paused after repeated failures until {}",
+                until
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BackendError {}
@@ -1,2 +1,5 @@
+pub mod circuit_breaker;
 pub mod client;
+pub mod credentials;
+pub mod error;
 pub mod objects;
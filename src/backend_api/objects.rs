@@ -1,3 +1,4 @@
+use chrono::{DateTime, NaiveDateTime};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -80,3 +81,115 @@ pub struct TokenUnits {
 }
 
 pub type Account = HashMap<String, HashMap<String, Vec<TokenUnits>>>;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BotLogEntry {
+    #[serde(default)]
+    pub level_name: Option<String>,
+    pub msg: String,
+    pub timestamp: f64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BotStatus {
+    pub status: String,
+    #[serde(default)]
+    pub performance: HashMap<String, Controller>,
+    #[serde(default)]
+    pub error_logs: Vec<BotLogEntry>,
+    #[serde(default)]
+    pub general_logs: Vec<BotLogEntry>,
+    #[serde(default)]
+    pub recently_active: bool,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BotStatusResponse {
+    pub status: String,
+    pub data: BotStatus,
+}
+
+/// The accounts as saved by the backend at `timestamp`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountStateSnapshot {
+    pub timestamp: String,
+    pub state: Account,
+}
+
+impl AccountStateSnapshot {
+    /// Seconds since the epoch, the backend leaves out the time zone when it's UTC
+    #[allow(dead_code)]
+    pub fn unix_timestamp(&self) -> Option<i64> {
+        if let Ok(time) = DateTime::parse_from_rfc3339(&self.timestamp) {
+            return Some(time.timestamp());
+        }
+        NaiveDateTime::parse_from_str(&self.timestamp, "%Y-%m-%dT%H:%M:%S%.f")
+            .ok()
+            .map(|time| time.and_utc().timestamp())
+    }
+}
+
+/// A saved controller config, settings that differ per controller type are kept in `extra`
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ControllerConfig {
+    pub id: String,
+    pub controller_name: String,
+    #[serde(default)]
+    pub controller_type: Option<String>,
+    #[serde(default)]
+    pub connector_name: Option<String>,
+    #[serde(default)]
+    pub trading_pair: Option<String>,
+    #[serde(default)]
+    pub total_amount_quote: Option<Decimal>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct PortfolioHistoryFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_names: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connector_names: Option<Vec<String>>,
+    /// Unix timestamps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct Pagination {
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub has_more: bool,
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortfolioHistoryResponse {
+    pub data: Vec<AccountStateSnapshot>,
+    #[serde(default)]
+    pub pagination: Pagination,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct StartBotRequest {
+    pub bot_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    /// Script to run, e.g. `v2_with_controllers.py`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Config file of the script
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conf: Option<String>,
+    pub async_backend: bool,
+}
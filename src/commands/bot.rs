@@ -0,0 +1,257 @@
+use std::time::Duration;
+
+use log::{info, warn};
+use poise::serenity_prelude::{
+    ButtonStyle, ComponentInteractionCollector, CreateActionRow, CreateButton, CreateEmbed,
+    CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage,
+};
+use poise::CreateReply;
+
+use super::permissions::can_control_bots;
+use crate::backend_api::client::BackendAPIClient;
+use crate::backend_api::error::BackendError;
+use crate::backend_api::objects::StartBotRequest;
+use crate::i18n::Locale;
+use crate::utils::truncate::truncate;
+use crate::{
+    autocomplete_backend, ctx_locale, select_backends, tag_embed, Context, Error,
+    ERROR_PREVIEW_LENGTH,
+};
+
+/// Start and stop bots on the backend
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("bot_start", "bot_stop", "bot_stop_controller"),
+    subcommand_required,
+    guild_only,
+    check = "can_control_bots"
+)]
+pub async fn bot(_ctx: Context<'_, '_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Start a bot
+#[poise::command(slash_command, prefix_command, rename = "start")]
+async fn bot_start(
+    ctx: Context<'_, '_>,
+    #[description = "Name of the new bot"] bot: String,
+    #[description = "Script to run, e.g. v2_with_controllers.py"] script: Option<String>,
+    #[description = "Config file of the script"] conf: Option<String>,
+    #[description = "Backend to start the bot on"]
+    #[autocomplete = "autocomplete_backend"]
+    backend: Option<String>,
+) -> Result<(), Error> {
+    let request = StartBotRequest {
+        bot_name: bot.trim().to_string(),
+        log_level: None,
+        script,
+        conf,
+        async_backend: false,
+    };
+    run(ctx, backend, BotAction::Start(request)).await
+}
+
+/// Stop a bot, cancelling its open orders
+#[poise::command(slash_command, prefix_command, rename = "stop")]
+async fn bot_stop(
+    ctx: Context<'_, '_>,
+    #[description = "Full bot name"] bot: String,
+    #[description = "Leave the open orders of the bot in place"] keep_orders: Option<bool>,
+    #[description = "Backend the bot runs on"]
+    #[autocomplete = "autocomplete_backend"]
+    backend: Option<String>,
+) -> Result<(), Error> {
+    let action = BotAction::Stop {
+        bot: bot.trim().to_string(),
+        keep_orders: keep_orders.unwrap_or(false),
+    };
+    run(ctx, backend, action).await
+}
+
+/// Stop one controller of a bot, the bot keeps running
+#[poise::command(slash_command, prefix_command, rename = "stop-controller")]
+async fn bot_stop_controller(
+    ctx: Context<'_, '_>,
+    #[description = "Full bot name"] bot: String,
+    #[description = "Controller id, as shown in /stats"] controller: String,
+    #[description = "Backend the bot runs on"]
+    #[autocomplete = "autocomplete_backend"]
+    backend: Option<String>,
+) -> Result<(), Error> {
+    let action = BotAction::StopController {
+        bot: bot.trim().to_string(),
+        controller: controller.trim().to_string(),
+    };
+    run(ctx, backend, action).await
+}
+
+enum BotAction {
+    Start(StartBotRequest),
+    Stop { bot: String, keep_orders: bool },
+    StopController { bot: String, controller: String },
+}
+
+impl BotAction {
+    fn describe(&self, locale: &Locale) -> String {
+        match self {
+            BotAction::Start(request) => {
+                locale.tf("bot_control.start", &[("bot", request.bot_name.clone())])
+            }
+            BotAction::Stop { bot, keep_orders } => {
+                let key = if *keep_orders {
+                    "bot_control.stop_keep_orders"
+                } else {
+                    "bot_control.stop"
+                };
+                locale.tf(key, &[("bot", bot.clone())])
+            }
+            BotAction::StopController { bot, controller } => locale.tf(
+                "bot_control.stop_controller",
+                &[("bot", bot.clone()), ("controller", controller.clone())],
+            ),
+        }
+    }
+
+    async fn execute(&self, client: &BackendAPIClient) -> Result<(), BackendError> {
+        match self {
+            BotAction::Start(request) => client.start_bot(request).await,
+            BotAction::Stop { bot, keep_orders } => client.stop_bot(bot, *keep_orders).await,
+            BotAction::StopController { bot, controller } => {
+                client.stop_controller(bot, controller).await
+            }
+        }
+    }
+}
+
+/// Asks the user to confirm the action, runs it and posts the outcome to the ops channel
+async fn run(
+    ctx: Context<'_, '_>,
+    backend: Option<String>,
+    action: BotAction,
+) -> Result<(), Error> {
+    let locale = ctx_locale(ctx);
+    let config = ctx.data().config.get();
+    let Some(selected) = select_backends(ctx, &locale, backend.as_deref()).await? else {
+        return Ok(());
+    };
+    let [backend] = selected[..] else {
+        ephemeral(ctx, locale.t("bot_control.choose_backend")).await?;
+        return Ok(());
+    };
+    let label = ctx.data().backends.label(backend);
+    let mut description = action.describe(&locale);
+    if let Some(label) = label {
+        description = format!("{} ({})", description, label);
+    }
+
+    let confirm_id = format!("{}confirm", ctx.id());
+    let cancel_id = format!("{}cancel", ctx.id());
+    let buttons = CreateActionRow::Buttons(vec![
+        CreateButton::new(&confirm_id)
+            .style(ButtonStyle::Danger)
+            .label(locale.t("bot_control.confirm_button")),
+        CreateButton::new(&cancel_id)
+            .style(ButtonStyle::Secondary)
+            .label(locale.t("bot_control.cancel_button")),
+    ]);
+    let prompt = locale.tf("bot_control.confirm", &[("action", description.clone())]);
+    let reply = ctx
+        .send(
+            CreateReply::default()
+                .ephemeral(true)
+                .content(prompt)
+                .components(vec![buttons]),
+        )
+        .await?;
+
+    let ids = (confirm_id.clone(), cancel_id);
+    let timeout = Duration::from_secs(config.bot_control.confirmation_timeout_seconds);
+    let Some(interaction) = ComponentInteractionCollector::new(ctx)
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(timeout)
+        .filter(move |i| i.data.custom_id == ids.0 || i.data.custom_id == ids.1)
+        .await
+    else {
+        let expired = locale.tf("bot_control.expired", &[("action", description)]);
+        reply
+            .edit(
+                ctx,
+                CreateReply::default().content(expired).components(vec![]),
+            )
+            .await?;
+        return Ok(());
+    };
+    let confirmed = interaction.data.custom_id == confirm_id;
+    let key = if confirmed {
+        "bot_control.running"
+    } else {
+        "bot_control.cancelled"
+    };
+    let message = CreateInteractionResponseMessage::new()
+        .content(locale.tf(key, &[("action", description.clone())]))
+        .components(vec![]);
+    interaction
+        .create_response(ctx, CreateInteractionResponse::UpdateMessage(message))
+        .await?;
+    if !confirmed {
+        return Ok(());
+    }
+
+    let user = ctx.author();
+    let result = action.execute(&backend.client).await;
+    let (outcome, color) = match &result {
+        Ok(()) => {
+            info!(
+                "{} ({}) did on backend {}: {}",
+                user.name, user.id, backend.name, description
+            );
+            (
+                locale.tf("bot_control.done", &[("action", description.clone())]),
+                0x41d321,
+            )
+        }
+        Err(e) => {
+            warn!(
+                "{} ({}) failed on backend {}: {}: {}",
+                user.name, user.id, backend.name, description, e
+            );
+            let variables = [
+                ("action", description.clone()),
+                ("error", truncate(&e.to_string(), ERROR_PREVIEW_LENGTH)),
+            ];
+            (locale.tf("bot_control.failed", &variables), 0xd32f2f)
+        }
+    };
+    reply
+        .edit(ctx, CreateReply::default().content(outcome.clone()))
+        .await?;
+
+    let embed = CreateEmbed::new()
+        .title(locale.t("bot_control.audit_title"))
+        .description(outcome)
+        .field(
+            locale.t("bot_control.audit_user"),
+            format!("<@{}>", user.id),
+            true,
+        )
+        .color(color);
+    let ops_channel = config.backend_ops_channel(&backend.name);
+    if let Err(e) = ops_channel
+        .send_message(ctx, CreateMessage::new().add_embed(tag_embed(embed, label)))
+        .await
+    {
+        warn!(
+            "Error (Ignored) posting the bot control audit message: {}",
+            e
+        );
+    }
+    Ok(())
+}
+
+async fn ephemeral(ctx: Context<'_, '_>, content: String) -> Result<(), Error> {
+    ctx.send(CreateReply::default().ephemeral(true).content(content))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use poise::serenity_prelude::{CreateEmbed, CreateEmbedFooter};
+use poise::CreateReply;
+
+use super::permissions::can_view_trades;
+use crate::utils::extract_bot_name::parse_bot_name;
+use crate::utils::truncate::truncate;
+use crate::{
+    autocomplete_backend, ctx_locale, select_backends, Context, Error, ERROR_PREVIEW_LENGTH,
+};
+
+/// List the running bots with their version and deploy time
+#[poise::command(slash_command, prefix_command, check = "can_view_trades")]
+pub async fn bots(
+    ctx: Context<'_, '_>,
+    #[description = "Only show this backend"]
+    #[autocomplete = "autocomplete_backend"]
+    backend: Option<String>,
+) -> Result<(), Error> {
+    let locale = ctx_locale(ctx);
+    let Some(selected) = select_backends(ctx, &locale, backend.as_deref()).await? else {
+        return Ok(());
+    };
+    ctx.defer().await?;
+    let naming = ctx.data().config.get().naming();
+    let settings = ctx.data().guilds.get(ctx.guild_id());
+    let mut reply = CreateReply::default();
+    // Discord allows up to 10 embeds per message
+    for backend in selected.into_iter().take(10) {
+        let mut embed = CreateEmbed::new().title(match ctx.data().backends.label(backend) {
+            Some(label) => format!("{} · {}", locale.t("bots.title"), label),
+            None => locale.t("bots.title"),
+        });
+        let mut bots = match backend.client.get_bots().await {
+            Ok(response) => response.data.into_iter().collect::<Vec<_>>(),
+            Err(e) => {
+                let error = truncate(&e.to_string(), ERROR_PREVIEW_LENGTH);
+                reply = reply.embed(
+                    embed
+                        .description(locale.tf("bots.error", &[("error", error)]))
+                        .color(0xd32f2f),
+                );
+                continue;
+            }
+        };
+        bots.retain(|(name, _)| settings.shows_bot(name));
+        bots.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if bots.is_empty() {
+            embed = embed.description(locale.t("bots.none"));
+        }
+        // Discord allows up to 25 fields per embed
+        for (name, bot) in bots.iter().take(25) {
+            let parsed = parse_bot_name(name);
+            let mut lines = vec![
+                format!("`{}`", name),
+                locale.tf("bots.status", &[("status", bot.status.clone())]),
+            ];
+            if let Some(version) = parsed.version {
+                lines.push(locale.tf("bots.version", &[("version", version.to_string())]));
+            }
+            if let Some(deployed) = parsed.deployed_at {
+                lines.push(locale.tf(
+                    "bots.deployed",
+                    &[(
+                        "deployed",
+                        format!("<t:{}:f> (<t:{}:R>)", deployed, deployed),
+                    )],
+                ));
+            }
+            embed = embed.field(naming.bot(name), lines.join("\n"), true);
+        }
+        if bots.len() > 25 {
+            embed = embed.footer(CreateEmbedFooter::new(
+                locale.tf("bots.more", &[("count", (bots.len() - 25).to_string())]),
+            ));
+        }
+        reply = reply.embed(embed.color(0x41d321));
+    }
+    ctx.send(reply).await?;
+    Ok(())
+}
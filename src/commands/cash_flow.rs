@@ -0,0 +1,153 @@
+use poise::CreateReply;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+use super::permissions::{can_manage_config, can_view_balances};
+use crate::structs::cash_flow::{CashFlow, CashFlowSource};
+use crate::utils::unix_timestamp::unix_timestamp;
+use crate::{autocomplete_backend, ctx_locale, select_backends, Context, Error};
+
+/// Manage deposits and withdrawals, so they aren't counted as profit
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("cash_flow_record", "cash_flow_list"),
+    subcommand_required,
+    check = "can_view_balances"
+)]
+pub async fn cash_flow(_ctx: Context<'_, '_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Record a deposit or withdrawal for an account
+#[poise::command(
+    slash_command,
+    prefix_command,
+    rename = "record",
+    check = "can_manage_config"
+)]
+async fn cash_flow_record(
+    ctx: Context<'_, '_>,
+    #[description = "Account name as shown in the charts"] account: String,
+    #[description = "Amount in the reporting currency, negative for withdrawals"] amount: String,
+    #[description = "What the flow was for"] note: Option<String>,
+    #[description = "Backend of the account, needed when there are several"]
+    #[autocomplete = "autocomplete_backend"]
+    backend: Option<String>,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let locale = ctx_locale(ctx);
+    let Some(selected) = select_backends(ctx, &locale, backend.as_deref()).await? else {
+        return Ok(());
+    };
+    let [backend] = selected[..] else {
+        let builder = CreateReply::default()
+            .ephemeral(true)
+            .content(locale.t("backend.choose"));
+        ctx.send(builder).await?;
+        return Ok(());
+    };
+    let Ok(amount) = Decimal::from_str(amount.trim()) else {
+        let builder = CreateReply::default()
+            .ephemeral(true)
+            .content(locale.tf("cash_flow.invalid_amount", &[("amount", amount)]));
+        ctx.send(builder).await?;
+        return Ok(());
+    };
+    // The account can be given by the name it's shown with
+    let naming = data.config.get().naming();
+    let account = backend
+        .balances
+        .get_balances()?
+        .iter()
+        .flat_map(|balance| balance.accounts.keys())
+        .find(|name| naming.account(name).eq_ignore_ascii_case(account.trim()))
+        .cloned()
+        .unwrap_or(account);
+    let rate = backend.balances.cached_rate();
+    backend.balances.record_cash_flow(CashFlow {
+        account: account.clone(),
+        amount,
+        timestamp: unix_timestamp(),
+        source: CashFlowSource::Manual,
+        note,
+        conversion: Some(rate.clone()),
+    })?;
+    let builder = CreateReply::default().ephemeral(true).content(locale.tf(
+        if amount.is_sign_negative() {
+            "cash_flow.recorded_withdrawal"
+        } else {
+            "cash_flow.recorded_deposit"
+        },
+        &[
+            ("amount", locale.format_amount(amount.abs(), &rate.to)),
+            ("currency", rate.to.clone()),
+            (
+                "account",
+                data.backends.tag(backend, &naming.account(&account)),
+            ),
+        ],
+    ));
+    ctx.send(builder).await?;
+    Ok(())
+}
+
+/// List the most recent deposits and withdrawals
+#[poise::command(slash_command, prefix_command, rename = "list")]
+async fn cash_flow_list(
+    ctx: Context<'_, '_>,
+    #[description = "Only list the flows of this backend"]
+    #[autocomplete = "autocomplete_backend"]
+    backend: Option<String>,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let locale = ctx_locale(ctx);
+    let Some(selected) = select_backends(ctx, &locale, backend.as_deref()).await? else {
+        return Ok(());
+    };
+    let naming = data.config.get().naming();
+    let mut flows = vec![];
+    for backend in selected {
+        let rate = backend.balances.cached_rate();
+        for flow in backend.balances.get_cash_flows()? {
+            flows.push((
+                data.backends.tag(backend, &naming.account(&flow.account)),
+                flow,
+                rate.clone(),
+            ));
+        }
+    }
+    flows.sort_by_key(|(_, flow, _)| flow.timestamp);
+    let lines: Vec<String> = flows
+        .iter()
+        .rev()
+        .take(20)
+        .map(|(account, flow, rate)| {
+            let amount = flow.amount * flow.rebase_factor(rate);
+            format!(
+                "<t:{}:f> **{}** {}{} {} ({}){}",
+                flow.timestamp,
+                account,
+                if amount.is_sign_positive() { "+" } else { "" },
+                locale.format_amount(amount, &rate.to),
+                rate.to,
+                locale.t(match flow.source {
+                    CashFlowSource::Manual => "cash_flow.source.manual",
+                    CashFlowSource::Detected => "cash_flow.source.detected",
+                }),
+                flow.note
+                    .as_ref()
+                    .map(|note| format!(": {}", note))
+                    .unwrap_or_default()
+            )
+        })
+        .collect();
+    let content = if lines.is_empty() {
+        locale.t("cash_flow.none")
+    } else {
+        lines.join("\n")
+    };
+    ctx.send(CreateReply::default().ephemeral(true).content(content))
+        .await?;
+    Ok(())
+}
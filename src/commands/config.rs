@@ -0,0 +1,240 @@
+use poise::serenity_prelude::{self as serenity, CreateEmbed};
+use poise::CreateReply;
+
+use super::permissions::can_manage_config;
+use crate::config::{Config, TradeRoute};
+use crate::{ctx_locale, Context, Error};
+
+/// View and change the bot's configuration, changes are written to the config file
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands(
+        "config_show",
+        "config_schedule",
+        "config_message",
+        "config_announcement_channel",
+        "config_announcements",
+        "config_trades_channel",
+        "config_muted_bots",
+        "config_route_add",
+        "config_route_remove"
+    ),
+    subcommand_required,
+    check = "can_manage_config"
+)]
+pub async fn config(_ctx: Context<'_, '_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show the current configuration
+#[poise::command(slash_command, prefix_command, rename = "show")]
+async fn config_show(ctx: Context<'_, '_>) -> Result<(), Error> {
+    let config = ctx.data().config.get();
+    reply_config(ctx, &config).await
+}
+
+/// Change when the profit chart is announced
+#[poise::command(slash_command, prefix_command, rename = "schedule")]
+async fn config_schedule(
+    ctx: Context<'_, '_>,
+    #[description = "Cron expression with seconds, e.g. \"0 0 9 * * *\""] cron: String,
+) -> Result<(), Error> {
+    update(ctx, |c| {
+        c.scheduled_chart_announcement.schedule = cron.trim().to_string().into()
+    })
+    .await
+}
+
+/// Change the text of the scheduled announcement
+#[poise::command(slash_command, prefix_command, rename = "message")]
+async fn config_message(
+    ctx: Context<'_, '_>,
+    #[description = "Can use {pnl_24h}, {total_balance}, {currency} and {date}"] text: String,
+) -> Result<(), Error> {
+    update(ctx, |c| {
+        c.scheduled_chart_announcement.message = text.into()
+    })
+    .await
+}
+
+/// Change the default channel of the scheduled announcement
+#[poise::command(slash_command, prefix_command, rename = "announcement-channel")]
+async fn config_announcement_channel(
+    ctx: Context<'_, '_>,
+    #[description = "Channel for the scheduled profit charts"]
+    #[channel_types("Text", "News")]
+    channel: serenity::GuildChannel,
+) -> Result<(), Error> {
+    update(ctx, |c| {
+        c.scheduled_chart_announcement.channel_id = channel.id.get()
+    })
+    .await
+}
+
+/// Turn the scheduled announcement on or off by default
+#[poise::command(slash_command, prefix_command, rename = "announcements")]
+async fn config_announcements(
+    ctx: Context<'_, '_>,
+    #[description = "Post the scheduled profit charts"] enabled: bool,
+) -> Result<(), Error> {
+    update(ctx, |c| c.scheduled_chart_announcement.enabled = enabled).await
+}
+
+/// Change the default channel for trades
+#[poise::command(slash_command, prefix_command, rename = "trades-channel")]
+async fn config_trades_channel(
+    ctx: Context<'_, '_>,
+    #[description = "Channel for trade notifications"]
+    #[channel_types("Text", "News")]
+    channel: serenity::GuildChannel,
+) -> Result<(), Error> {
+    update(ctx, |c| c.stats_channel_id = channel.id.get()).await
+}
+
+/// Choose the bots whose trades aren't posted anywhere
+#[poise::command(slash_command, prefix_command, rename = "muted-bots")]
+async fn config_muted_bots(
+    ctx: Context<'_, '_>,
+    #[description = "Comma separated bot names, leave empty to unmute all bots"] bots: Option<
+        String,
+    >,
+) -> Result<(), Error> {
+    update(ctx, |c| c.muted_bots = parse_list(bots)).await
+}
+
+/// Also post the trades of some bots or pairs to a channel
+#[poise::command(slash_command, prefix_command, rename = "route-add")]
+async fn config_route_add(
+    ctx: Context<'_, '_>,
+    #[description = "Channel to post the matching trades to"]
+    #[channel_types("Text", "News")]
+    channel: serenity::GuildChannel,
+    #[description = "Comma separated bot names, any bot when empty"] bots: Option<String>,
+    #[description = "Comma separated pairs like BTC-USDT, any pair when empty"] pairs: Option<
+        String,
+    >,
+) -> Result<(), Error> {
+    update(ctx, |c| {
+        c.trade_routes.retain(|r| r.channel_id != channel.id.get());
+        c.trade_routes.push(TradeRoute {
+            channel_id: channel.id.get(),
+            bots: parse_list(bots),
+            pairs: parse_list(pairs),
+        });
+    })
+    .await
+}
+
+/// Stop posting trades to a channel added with route-add
+#[poise::command(slash_command, prefix_command, rename = "route-remove")]
+async fn config_route_remove(
+    ctx: Context<'_, '_>,
+    #[description = "Channel of the route"]
+    #[channel_types("Text", "News")]
+    channel: serenity::GuildChannel,
+) -> Result<(), Error> {
+    update(ctx, |c| {
+        c.trade_routes.retain(|r| r.channel_id != channel.id.get())
+    })
+    .await
+}
+
+fn parse_list(list: Option<String>) -> Vec<String> {
+    list.unwrap_or_default()
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+async fn update<'c>(
+    ctx: Context<'c, '_>,
+    change: impl FnOnce(&mut Config<'c>),
+) -> Result<(), Error> {
+    match ctx.data().config.update(change).await {
+        Ok(config) => reply_config(ctx, &config).await,
+        Err(e) => {
+            let locale = ctx_locale(ctx);
+            let builder = CreateReply::default()
+                .ephemeral(true)
+                .content(locale.tf("config.invalid", &[("error", e.to_string())]));
+            ctx.send(builder).await?;
+            Ok(())
+        }
+    }
+}
+
+async fn reply_config(ctx: Context<'_, '_>, config: &Config<'_>) -> Result<(), Error> {
+    let locale = ctx_locale(ctx);
+    let announcement = &config.scheduled_chart_announcement;
+    let list = |names: &[String]| {
+        if names.is_empty() {
+            locale.t("config.none")
+        } else {
+            names.join(", ")
+        }
+    };
+    let routes: Vec<String> = config
+        .trade_routes
+        .iter()
+        .map(|route| {
+            format!(
+                "<#{}>: {} / {}",
+                route.channel_id,
+                if route.bots.is_empty() {
+                    locale.t("setup.all")
+                } else {
+                    route.bots.join(", ")
+                },
+                if route.pairs.is_empty() {
+                    locale.t("setup.all")
+                } else {
+                    route.pairs.join(", ")
+                }
+            )
+        })
+        .collect();
+    let embed = CreateEmbed::new()
+        .title(locale.t("config.title"))
+        .fields(vec![
+            (
+                locale.t("config.schedule"),
+                format!("`{}`", announcement.schedule),
+                true,
+            ),
+            (
+                locale.t("setup.announcements"),
+                locale.t(if announcement.enabled {
+                    "setup.on"
+                } else {
+                    "setup.off"
+                }),
+                true,
+            ),
+            (
+                locale.t("setup.announcement_channel"),
+                format!("<#{}>", announcement.channel_id),
+                true,
+            ),
+            (
+                locale.t("config.message"),
+                announcement.message.to_string(),
+                false,
+            ),
+            (
+                locale.t("setup.trades_channel"),
+                format!("<#{}>", config.stats_channel_id),
+                true,
+            ),
+            (
+                locale.t("config.muted_bots"),
+                list(&config.muted_bots),
+                true,
+            ),
+            (locale.t("config.routes"), list(&routes), false),
+        ]);
+    ctx.send(CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use poise::serenity_prelude::CreateEmbed;
+use poise::CreateReply;
+
+use super::permissions::can_view_trades;
+use crate::utils::truncate::truncate;
+use crate::{
+    autocomplete_backend, ctx_locale, select_backends, Context, Error, ERROR_PREVIEW_LENGTH,
+};
+
+/// Show whether the backend API can be reached
+#[poise::command(slash_command, prefix_command, check = "can_view_trades")]
+pub async fn health(
+    ctx: Context<'_, '_>,
+    #[description = "Only show this backend"]
+    #[autocomplete = "autocomplete_backend"]
+    backend: Option<String>,
+) -> Result<(), Error> {
+    let locale = ctx_locale(ctx);
+    let Some(selected) = select_backends(ctx, &locale, backend.as_deref()).await? else {
+        return Ok(());
+    };
+    let config = ctx.data().config.get();
+    let threshold = config.health_checks.failure_threshold;
+    let mut reply = CreateReply::default();
+    // Discord allows up to 10 embeds per message
+    for backend in selected.into_iter().take(10) {
+        let status = backend.client.health().status();
+        let mut embed = CreateEmbed::new().title(match ctx.data().backends.label(backend) {
+            Some(label) => format!("{} · {}", locale.t("health.title"), label),
+            None => locale.t("health.title"),
+        });
+        if status.is_empty() {
+            embed = embed.description(locale.t("health.no_requests"));
+        }
+        for (endpoint, health) in status.iter() {
+            let key = if health.is_healthy() {
+                "health.status_ok"
+            } else if health.consecutive_failures >= threshold {
+                "health.status_down"
+            } else {
+                "health.status_failing"
+            };
+            let mut lines = vec![locale.tf(
+                key,
+                &[("failures", health.consecutive_failures.to_string())],
+            )];
+            if let Some(since) = health.failing_since.filter(|_| !health.is_healthy()) {
+                lines.push(locale.tf(
+                    "health.failing_since",
+                    &[("since", format!("<t:{}:R>", since))],
+                ));
+            }
+            if let Some(last) = health.last_success {
+                lines.push(locale.tf(
+                    "health.last_success",
+                    &[("last", format!("<t:{}:R>", last))],
+                ));
+            }
+            if let Some(error) = health.last_error.as_ref().filter(|_| !health.is_healthy()) {
+                lines.push(format!("```{}```", truncate(error, ERROR_PREVIEW_LENGTH)));
+            }
+            embed = embed.field(endpoint, lines.join("\n"), false);
+        }
+        let all_healthy = status.values().all(|h| h.is_healthy());
+        embed = embed.color(if all_healthy { 0x41d321 } else { 0xd32f2f });
+        reply = reply.embed(embed);
+    }
+    ctx.send(reply).await?;
+    Ok(())
+}
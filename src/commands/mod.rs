@@ -0,0 +1,10 @@
+pub mod bot;
+pub mod bots;
+pub mod cash_flow;
+pub mod config;
+pub mod health;
+pub mod mute;
+pub mod permissions;
+pub mod profit_chart;
+pub mod setup;
+pub mod stats;
@@ -0,0 +1,160 @@
+use poise::CreateReply;
+
+use super::permissions::can_manage_config;
+use crate::i18n::Locale;
+use crate::structs::mutes::{Mute, NotificationState};
+use crate::utils::duration::parse_duration;
+use crate::utils::unix_timestamp::unix_timestamp;
+use crate::{ctx_locale, Context, Error};
+
+/// Stop posting the trades of a bot for a while
+#[poise::command(slash_command, prefix_command, check = "can_manage_config")]
+pub async fn mute(
+    ctx: Context<'_, '_>,
+    #[description = "Short or full bot name"] bot: String,
+    #[description = "For how long, e.g. 30m, 2h or 1d, until unmuted when empty"] duration: Option<
+        String,
+    >,
+) -> Result<(), Error> {
+    let locale = ctx_locale(ctx);
+    let Some(until) = parse_until(ctx, &locale, duration).await? else {
+        return Ok(());
+    };
+    let bot = bot.trim().to_string();
+    let state = ctx.data().mutes.update(|state| {
+        state.mutes.retain(|m| !m.bot.eq_ignore_ascii_case(&bot));
+        state.mutes.push(Mute {
+            bot: bot.clone(),
+            until,
+        });
+    })?;
+    let message = match until {
+        Some(until) => locale.tf(
+            "mute.muted_until",
+            &[("bot", bot), ("until", format!("<t:{}:f>", until))],
+        ),
+        None => locale.tf("mute.muted", &[("bot", bot)]),
+    };
+    reply(ctx, &locale, message, &state).await
+}
+
+/// Post the trades of a muted bot again
+#[poise::command(slash_command, prefix_command, check = "can_manage_config")]
+pub async fn unmute(
+    ctx: Context<'_, '_>,
+    #[description = "Bot name as it was muted"] bot: String,
+) -> Result<(), Error> {
+    let locale = ctx_locale(ctx);
+    let bot = bot.trim().to_string();
+    let mut found = false;
+    let state = ctx.data().mutes.update(|state| {
+        let before = state.mutes.len();
+        state.mutes.retain(|m| !m.bot.eq_ignore_ascii_case(&bot));
+        found = state.mutes.len() != before;
+    })?;
+    let key = if found {
+        "mute.unmuted"
+    } else {
+        "mute.not_muted"
+    };
+    reply(ctx, &locale, locale.tf(key, &[("bot", bot)]), &state).await
+}
+
+/// Stop posting trades of all bots for a while
+#[poise::command(slash_command, prefix_command, check = "can_manage_config")]
+pub async fn pause_notifications(
+    ctx: Context<'_, '_>,
+    #[description = "For how long, e.g. 30m, 2h or 1d, until resumed when empty"] duration: Option<
+        String,
+    >,
+) -> Result<(), Error> {
+    let locale = ctx_locale(ctx);
+    let Some(until) = parse_until(ctx, &locale, duration).await? else {
+        return Ok(());
+    };
+    let state = ctx.data().mutes.update(|state| {
+        state.paused = true;
+        state.paused_until = until;
+    })?;
+    let message = match until {
+        Some(until) => locale.tf(
+            "mute.paused_until",
+            &[("until", format!("<t:{}:f>", until))],
+        ),
+        None => locale.t("mute.paused"),
+    };
+    reply(ctx, &locale, message, &state).await
+}
+
+/// Post trades again after /pause_notifications
+#[poise::command(slash_command, prefix_command, check = "can_manage_config")]
+pub async fn resume_notifications(ctx: Context<'_, '_>) -> Result<(), Error> {
+    let locale = ctx_locale(ctx);
+    let state = ctx.data().mutes.update(|state| {
+        state.paused = false;
+        state.paused_until = None;
+    })?;
+    reply(ctx, &locale, locale.t("mute.resumed"), &state).await
+}
+
+/// End of the mute or pause, `Some(None)` for one without end and `None` after
+/// telling the user the duration is invalid
+async fn parse_until(
+    ctx: Context<'_, '_>,
+    locale: &Locale,
+    duration: Option<String>,
+) -> Result<Option<Option<u64>>, Error> {
+    let Some(duration) = duration else {
+        return Ok(Some(None));
+    };
+    match parse_duration(&duration) {
+        Ok(duration) => Ok(Some(Some(unix_timestamp() + duration.as_secs()))),
+        Err(_) => {
+            let builder = CreateReply::default()
+                .ephemeral(true)
+                .content(locale.tf("mute.invalid_duration", &[("duration", duration)]));
+            ctx.send(builder).await?;
+            Ok(None)
+        }
+    }
+}
+
+async fn reply(
+    ctx: Context<'_, '_>,
+    locale: &Locale,
+    message: String,
+    state: &NotificationState,
+) -> Result<(), Error> {
+    let mut lines = vec![message];
+    if state.paused {
+        lines.push(match state.paused_until {
+            Some(until) => locale.tf(
+                "mute.paused_until",
+                &[("until", format!("<t:{}:R>", until))],
+            ),
+            None => locale.t("mute.paused"),
+        });
+    }
+    for mute in state.mutes.iter() {
+        lines.push(format!(
+            "-# {}",
+            match mute.until {
+                Some(until) => locale.tf(
+                    "mute.muted_until",
+                    &[
+                        ("bot", mute.bot.clone()),
+                        ("until", format!("<t:{}:R>", until))
+                    ],
+                ),
+                None => locale.tf("mute.muted", &[("bot", mute.bot.clone())]),
+            }
+        ));
+    }
+    ctx.send(
+        CreateReply::default()
+            .ephemeral(true)
+            .content(lines.join("\n")),
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use poise::CreateReply;
+
+use crate::config::Capability;
+use crate::{ctx_locale, Context, Error};
+
+pub async fn can_view_balances(ctx: Context<'_, '_>) -> Result<bool, Error> {
+    check(ctx, Capability::ViewBalances).await
+}
+
+pub async fn can_view_trades(ctx: Context<'_, '_>) -> Result<bool, Error> {
+    check(ctx, Capability::ViewTrades).await
+}
+
+pub async fn can_manage_config(ctx: Context<'_, '_>) -> Result<bool, Error> {
+    check(ctx, Capability::ManageConfig).await
+}
+
+pub async fn can_control_bots(ctx: Context<'_, '_>) -> Result<bool, Error> {
+    check(ctx, Capability::ControlBots).await
+}
+
+/// Whether the author may run the command, telling them why not when they can't
+async fn check(ctx: Context<'_, '_>, capability: Capability) -> Result<bool, Error> {
+    let (role_ids, administrator) = match ctx.author_member().await {
+        Some(member) => {
+            // Interactions come with the member's permissions, prefix commands use the cache
+            let permissions = member
+                .permissions
+                .or_else(|| ctx.guild().map(|guild| guild.member_permissions(&member)));
+            (
+                member.roles.iter().map(|r| r.get()).collect(),
+                permissions.is_some_and(|p| p.administrator()),
+            )
+        }
+        None => (vec![], false),
+    };
+    let allowed =
+        ctx.data()
+            .config
+            .get()
+            .allows(ctx.author().id.get(), &role_ids, administrator, capability);
+    if !allowed {
+        let name = serde_json::to_value(capability)?;
+        let message = ctx_locale(ctx).tf(
+            "permissions.denied",
+            &[("capability", name.as_str().unwrap_or_default().to_string())],
+        );
+        ctx.send(CreateReply::default().ephemeral(true).content(message))
+            .await?;
+    }
+    Ok(allowed)
+}
@@ -0,0 +1,88 @@
+use poise::serenity_prelude::{CreateAttachment, CreateMessage};
+
+use super::permissions::can_view_balances;
+use crate::structs::profit_chart::{ChartFormat, ChartTheme};
+use crate::{
+    autocomplete_backend, ctx_locale, make_chart, select_backends, shown_rate, update_balances,
+    with_rate_note, AccountLabels, Context, Error,
+};
+
+/// Displays a profit chart
+#[allow(clippy::too_many_arguments)]
+#[poise::command(slash_command, prefix_command, check = "can_view_balances")]
+pub async fn profit_chart(
+    ctx: Context<'_, '_>,
+    #[description = "Color theme, defaults to the configured theme"] theme: Option<ChartTheme>,
+    #[description = "Image format, defaults to the configured format"] format: Option<ChartFormat>,
+    #[description = "Chart width in pixels"]
+    #[min = 200]
+    #[max = 1920]
+    width: Option<u32>,
+    #[description = "Chart height in pixels"]
+    #[min = 150]
+    #[max = 1080]
+    height: Option<u32>,
+    #[description = "Pixel density, use 2 or more for high-DPI screens"]
+    #[min = 1.0]
+    #[max = 4.0]
+    scale: Option<f64>,
+    #[description = "Leave deposits and withdrawals out of the balance lines"] net_of_flows: Option<
+        bool,
+    >,
+    #[description = "Only show the accounts of this backend"]
+    #[autocomplete = "autocomplete_backend"]
+    backend: Option<String>,
+) -> Result<(), Error> {
+    let locale = ctx_locale(ctx);
+    let Some(selected) = select_backends(ctx, &locale, backend.as_deref()).await? else {
+        return Ok(());
+    };
+    let reply = ctx.reply(locale.t("profit_chart.starting")).await?;
+    let data = ctx.data();
+    let mut options = data.config.get().chart;
+    options.theme = theme.unwrap_or(options.theme);
+    options.format = format.unwrap_or(options.format);
+    options.width = width.unwrap_or(options.width);
+    options.height = height.unwrap_or(options.height);
+    options.device_scale_factor = scale.unwrap_or(options.device_scale_factor);
+    options.net_of_flows = net_of_flows.unwrap_or(options.net_of_flows);
+
+    let balances = update_balances(selected, true).await;
+    let rate = shown_rate(&balances);
+
+    let settings = data.guilds.get(ctx.guild_id());
+    let config = data.config.get();
+    let private = config.privacy.applies_to(ctx.channel_id());
+    let labels = AccountLabels::new(&config, &data.backends, &locale, private)?;
+    let graph = make_chart(
+        &data.backends,
+        &balances,
+        &options,
+        &locale,
+        &settings,
+        &labels,
+    )?;
+    if graph.is_empty() {
+        return Ok(());
+    }
+
+    ctx.channel_id()
+        .send_files(
+            ctx,
+            vec![CreateAttachment::bytes(graph, options.format.file_name())],
+            CreateMessage::default().content(with_rate_note(
+                &locale.t("profit_chart.caption"),
+                &rate,
+                &locale,
+            )),
+        )
+        .await?;
+
+    reply
+        .edit(
+            ctx,
+            poise::CreateReply::default().content(locale.t("profit_chart.done")),
+        )
+        .await?;
+    Ok(())
+}
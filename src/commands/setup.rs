@@ -0,0 +1,187 @@
+use poise::serenity_prelude::{self as serenity, ChannelId, CreateEmbed};
+use poise::CreateReply;
+
+use super::permissions::can_manage_config;
+use crate::i18n::Locale;
+use crate::structs::guild_settings::GuildSettings;
+use crate::{ctx_locale, Context, Error};
+
+/// Configure the bot for this server, anything not set here follows the config file
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands(
+        "setup_show",
+        "setup_trades_channel",
+        "setup_announcement_channel",
+        "setup_trades",
+        "setup_announcements",
+        "setup_visible_bots",
+        "setup_visible_accounts",
+        "setup_reset"
+    ),
+    subcommand_required,
+    guild_only,
+    check = "can_manage_config"
+)]
+pub async fn setup(_ctx: Context<'_, '_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show the settings of this server
+#[poise::command(slash_command, prefix_command, rename = "show")]
+async fn setup_show(ctx: Context<'_, '_>) -> Result<(), Error> {
+    let settings = ctx.data().guilds.get(ctx.guild_id());
+    reply_settings(ctx, &settings).await
+}
+
+/// Post trades to this channel
+#[poise::command(slash_command, prefix_command, rename = "trades-channel")]
+async fn setup_trades_channel(
+    ctx: Context<'_, '_>,
+    #[description = "Channel for trade notifications"]
+    #[channel_types("Text", "News")]
+    channel: serenity::GuildChannel,
+) -> Result<(), Error> {
+    update(ctx, |s| s.trades_channel_id = Some(channel.id.get())).await
+}
+
+/// Post the scheduled announcements to this channel
+#[poise::command(slash_command, prefix_command, rename = "announcement-channel")]
+async fn setup_announcement_channel(
+    ctx: Context<'_, '_>,
+    #[description = "Channel for the scheduled profit charts"]
+    #[channel_types("Text", "News")]
+    channel: serenity::GuildChannel,
+) -> Result<(), Error> {
+    update(ctx, |s| s.announcement_channel_id = Some(channel.id.get())).await
+}
+
+/// Turn trade notifications on or off
+#[poise::command(slash_command, prefix_command, rename = "trades")]
+async fn setup_trades(
+    ctx: Context<'_, '_>,
+    #[description = "Post a message for every trade"] enabled: bool,
+) -> Result<(), Error> {
+    update(ctx, |s| s.trades_enabled = Some(enabled)).await
+}
+
+/// Turn the scheduled announcements on or off
+#[poise::command(slash_command, prefix_command, rename = "announcements")]
+async fn setup_announcements(
+    ctx: Context<'_, '_>,
+    #[description = "Post the scheduled profit charts"] enabled: bool,
+) -> Result<(), Error> {
+    update(ctx, |s| s.announcements_enabled = Some(enabled)).await
+}
+
+/// Choose which bots this server sees trades of
+#[poise::command(slash_command, prefix_command, rename = "visible-bots")]
+async fn setup_visible_bots(
+    ctx: Context<'_, '_>,
+    #[description = "Comma separated bot names, leave empty to show all bots"] bots: Option<String>,
+) -> Result<(), Error> {
+    update(ctx, |s| s.visible_bots = parse_list(bots)).await
+}
+
+/// Choose which accounts show up in the charts and stats of this server
+#[poise::command(slash_command, prefix_command, rename = "visible-accounts")]
+async fn setup_visible_accounts(
+    ctx: Context<'_, '_>,
+    #[description = "Comma separated account names as shown or as in the backend, leave empty to show all accounts"]
+    accounts: Option<String>,
+) -> Result<(), Error> {
+    update(ctx, |s| s.visible_accounts = parse_list(accounts)).await
+}
+
+/// Go back to the defaults of the config file
+#[poise::command(slash_command, prefix_command, rename = "reset")]
+async fn setup_reset(ctx: Context<'_, '_>) -> Result<(), Error> {
+    update(ctx, |s| *s = GuildSettings::default()).await
+}
+
+fn parse_list(list: Option<String>) -> Option<Vec<String>> {
+    let names: Vec<String> = list?
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+    (!names.is_empty()).then_some(names)
+}
+
+async fn update(
+    ctx: Context<'_, '_>,
+    change: impl FnOnce(&mut GuildSettings),
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().ok_or("Only available in servers")?;
+    let settings = ctx.data().guilds.update(guild_id, change)?;
+    reply_settings(ctx, &settings).await
+}
+
+async fn reply_settings(ctx: Context<'_, '_>, settings: &GuildSettings) -> Result<(), Error> {
+    let locale = ctx_locale(ctx);
+    let config = ctx.data().config.get();
+    // The channels of the config file only apply to the server they are in
+    let is_default_guild = |id: u64| {
+        let guild_id = ctx.cache().channel(ChannelId::new(id)).map(|c| c.guild_id);
+        guild_id.is_some() && guild_id == ctx.guild_id()
+    };
+    let channel = |id: Option<u64>, default: u64| match id {
+        Some(id) => format!("<#{}>", id),
+        None if is_default_guild(default) => {
+            locale.tf("setup.default", &[("value", format!("<#{}>", default))])
+        }
+        None => locale.t("setup.not_set"),
+    };
+    let toggle = |enabled: Option<bool>, default: bool| match enabled {
+        Some(enabled) => on_off(&locale, enabled),
+        None => locale.tf("setup.default", &[("value", on_off(&locale, default))]),
+    };
+    let list = |names: &Option<Vec<String>>| {
+        names
+            .as_ref()
+            .map_or(locale.t("setup.all"), |names| names.join(", "))
+    };
+    let announcement = &config.scheduled_chart_announcement;
+    let embed = CreateEmbed::new()
+        .title(locale.t("setup.title"))
+        .fields(vec![
+            (
+                locale.t("setup.trades_channel"),
+                channel(settings.trades_channel_id, config.stats_channel_id),
+                true,
+            ),
+            (
+                locale.t("setup.trades"),
+                toggle(settings.trades_enabled, true),
+                true,
+            ),
+            (
+                locale.t("setup.visible_bots"),
+                list(&settings.visible_bots),
+                false,
+            ),
+            (
+                locale.t("setup.announcement_channel"),
+                channel(settings.announcement_channel_id, announcement.channel_id),
+                true,
+            ),
+            (
+                locale.t("setup.announcements"),
+                toggle(settings.announcements_enabled, announcement.enabled),
+                true,
+            ),
+            (
+                locale.t("setup.visible_accounts"),
+                list(&settings.visible_accounts),
+                false,
+            ),
+        ]);
+    ctx.send(CreateReply::default().ephemeral(true).embed(embed))
+        .await?;
+    Ok(())
+}
+
+fn on_off(locale: &Locale, enabled: bool) -> String {
+    locale.t(if enabled { "setup.on" } else { "setup.off" })
+}
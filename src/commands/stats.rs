@@ -0,0 +1,48 @@
+use poise::CreateReply;
+
+use super::permissions::can_view_balances;
+use crate::{
+    account_stats, autocomplete_backend, ctx_locale, select_backends, shown_rate, stats_embed,
+    update_balances, AccountLabels, Context, Error,
+};
+
+/// Shows performance statistics per account
+#[poise::command(slash_command, prefix_command, check = "can_view_balances")]
+pub async fn stats(
+    ctx: Context<'_, '_>,
+    #[description = "Only use the last number of days, defaults to all history"]
+    #[min = 1]
+    days: Option<u32>,
+    #[description = "Only show the accounts of this backend"]
+    #[autocomplete = "autocomplete_backend"]
+    backend: Option<String>,
+) -> Result<(), Error> {
+    let locale = ctx_locale(ctx);
+    let Some(selected) = select_backends(ctx, &locale, backend.as_deref()).await? else {
+        return Ok(());
+    };
+    ctx.defer().await?;
+    let data = ctx.data();
+    let balances = update_balances(selected, true).await;
+    let rate = shown_rate(&balances);
+    let settings = data.guilds.get(ctx.guild_id());
+    let config = data.config.get();
+    let private = config.privacy.applies_to(ctx.channel_id());
+    let labels = AccountLabels::new(&config, &data.backends, &locale, private)?;
+    let stats = account_stats(&data.backends, &balances, days, &settings, &labels)?;
+    if stats.is_empty() {
+        ctx.say(locale.t("stats.not_enough_history")).await?;
+        return Ok(());
+    }
+
+    let mut reply = CreateReply::default();
+    if let Some(note) = rate.note(&locale) {
+        reply = reply.content(format!("-# {}", note));
+    }
+    // Discord allows up to 10 embeds per message
+    for (account, stats) in stats.iter().take(10) {
+        reply = reply.embed(stats_embed(account, stats, &rate.to, &locale, private));
+    }
+    ctx.send(reply).await?;
+    Ok(())
+}
@@ -1,26 +1,696 @@
-use std::{borrow::Cow, path::PathBuf};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
 
+use anyhow::{anyhow, Result};
+use poise::serenity_prelude::ChannelId;
+use regex::Regex;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tokio_cron_scheduler::Job;
 use url::Url;
 
-#[derive(Serialize, Deserialize)]
+use crate::i18n::{self, parse_timezone, Locale, Locales, DEFAULT_LOCALE};
+use crate::structs::naming::Naming;
+use crate::structs::profit_chart::ChartOptions;
+use crate::structs::trade::Trade;
+use crate::utils::extract_bot_name::{matches_bot_name, BotNameParser, DEFAULT_BOT_NAME_PATTERN};
+use crate::utils::number_format;
+use crate::utils::template::Template;
+
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ScheduledStats<'c> {
     pub message: Cow<'c, str>,
     pub schedule: Cow<'c, str>,
     pub enabled: bool,
     pub channel_id: u64,
+    /// Add the performance stats of every account to the announcement
+    #[serde(default)]
+    pub include_stats: bool,
+    /// Number of days the announced stats cover, all history when empty
+    #[serde(default)]
+    pub stats_period_days: Option<u32>,
+}
+
+/// Where the price of the reporting currency comes from
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PriceSource {
+    /// Token prices from the backend's account state, requires holding the reporting currency
+    Backend,
+    /// Fixed prices of one unit of each currency, in the backend quote currency
+    #[serde(rename_all = "camelCase")]
+    Static { prices: HashMap<String, Decimal> },
+    /// JSON endpoint where `{base}` and `{quote}` in the URL are replaced by the currencies
+    #[serde(rename_all = "camelCase")]
+    Http {
+        url: String,
+        /// JSON pointer to the price in the response, e.g. `/price`
+        price_pointer: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReportingCurrency {
+    /// Currency balances and PnL are displayed in, e.g. USDT, USD, EUR or BTC
+    pub currency: String,
+    /// Currency the backend reports token values in
+    pub backend_quote: String,
+    pub price_source: PriceSource,
+}
+
+impl Default for ReportingCurrency {
+    fn default() -> Self {
+        Self {
+            currency: "USDT".into(),
+            backend_quote: "USDT".into(),
+            price_source: PriceSource::Backend,
+        }
+    }
+}
+
+/// Variables available in the trade embed templates
+pub const TRADE_TEMPLATE_VARIABLES: &[&str] = &[
+    "bot",
+    "bot_full",
+    "pair",
+    "base",
+    "quote",
+    "side",
+    "amount",
+    "price",
+    "notional",
+    "exchange",
+    "backend",
+    "pnl_24h",
+    "total_balance",
+    "currency",
+];
+
+/// Trade template variables that take reading the balance history
+const SUMMARY_VARIABLES: &[&str] = &["pnl_24h", "total_balance", "currency"];
+
+/// Variables available in the announcement message template
+pub const ANNOUNCEMENT_TEMPLATE_VARIABLES: &[&str] =
+    &["pnl_24h", "total_balance", "currency", "date"];
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbedFieldTemplate {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub inline: bool,
+}
+
+/// Layout of the message posted for every trade, text supports `{variable}` placeholders
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeEmbedTemplate {
+    pub title: String,
+    pub description: String,
+    pub fields: Vec<EmbedFieldTemplate>,
+    /// Hex color like `#41d321`
+    pub buy_color: String,
+    pub sell_color: String,
+}
+
+impl TradeEmbedTemplate {
+    /// Whether the layout shows any of the balance summary
+    pub fn uses_summary(&self) -> bool {
+        let texts = [&self.title, &self.description].into_iter().chain(
+            self.fields
+                .iter()
+                .flat_map(|field| [&field.name, &field.value]),
+        );
+        texts
+            .filter_map(|text| Template::parse(text).ok())
+            .any(|template| SUMMARY_VARIABLES.iter().any(|name| template.uses(name)))
+    }
+
+    /// The built-in layout, in the language of `locale`
+    pub fn localized(locale: &Locale) -> Self {
+        Self::with_fields(
+            locale,
+            &[
+                ("trade.field.bot", "{bot}", false),
+                ("trade.field.amount", "{amount} {base}", true),
+                ("trade.field.price", "{price} {quote}", true),
+                ("trade.field.notional", "{notional} {quote}", true),
+            ],
+        )
+    }
+
+    /// The built-in layout for private channels, with the trade value as a share of
+    /// the balance instead of the amounts and price
+    pub fn localized_private(locale: &Locale) -> Self {
+        Self::with_fields(
+            locale,
+            &[
+                ("trade.field.bot", "{bot}", false),
+                ("trade.field.share", "{notional}", true),
+            ],
+        )
+    }
+
+    /// Fields by translation key, value template and whether they're inline
+    fn with_fields(locale: &Locale, fields: &[(&str, &str, bool)]) -> Self {
+        Self {
+            title: locale.t("trade.title"),
+            description: locale.t("trade.description"),
+            fields: fields
+                .iter()
+                .map(|(key, value, inline)| EmbedFieldTemplate {
+                    name: locale.t(key),
+                    value: value.to_string(),
+                    inline: *inline,
+                })
+                .collect(),
+            buy_color: "#41d321".into(),
+            sell_color: "#d32121".into(),
+        }
+    }
+}
+
+pub fn parse_color(color: &str) -> Result<u32> {
+    u32::from_str_radix(color.trim().trim_start_matches('#'), 16)
+        .map_err(|e| anyhow!("Invalid color \"{}\": {}", color, e))
+}
+
+/// Automatic detection of deposits and withdrawals between balance snapshots
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CashFlowDetection {
+    pub enabled: bool,
+    /// Smallest flow to record, in the reporting currency
+    pub min_value: Decimal,
+    /// Smallest flow to record, in percent of the account balance
+    pub min_pct: Decimal,
+}
+
+impl Default for CashFlowDetection {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_value: Decimal::TEN,
+            min_pct: Decimal::TWO,
+        }
+    }
+}
+
+/// Budget of trade messages per channel, trades over it are posted as a digest
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RateLimit {
+    pub enabled: bool,
+    /// Trade messages allowed per channel within `window_seconds`
+    pub max_messages: usize,
+    pub window_seconds: u64,
+    /// How long trades are collected before the digest is posted
+    pub digest_interval_seconds: u64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_messages: 10,
+            window_seconds: 60,
+            digest_interval_seconds: 300,
+        }
+    }
+}
+
+/// A password or token, written in the config or read from a file like a Docker secret
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Secret {
+    Value(String),
+    File { file: PathBuf },
+}
+
+impl Secret {
+    pub fn reveal(&self) -> Result<String> {
+        match self {
+            Secret::Value(value) => Ok(value.clone()),
+            Secret::File { file } => read_secret_file(file),
+        }
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Secret::Value(_) => write!(f, "Secret(redacted)"),
+            Secret::File { file } => write!(f, "Secret(file: {})", file.display()),
+        }
+    }
+}
+
+/// Contents of a secrets file without the trailing newline editors add
+pub fn read_secret_file(path: &Path) -> Result<String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Can't read secret file {}: {}", path.display(), e))?;
+    Ok(contents.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// How the bot logs in to the backend API
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BackendAuth {
+    None,
+    Basic { username: String, password: Secret },
+    Bearer { token: Secret },
+}
+
+/// Name of the backend from `backendApiBaseUrl`, when no `backends` are configured
+pub const DEFAULT_BACKEND: &str = "default";
+
+/// One of several backend API deploys, e.g. paper and live trading
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendConfig {
+    /// Shown next to its bots, accounts and trades
+    pub name: String,
+    pub base_url: Url,
+    /// Login for this backend, none when empty
+    #[serde(default)]
+    pub auth: Option<BackendAuth>,
+    #[serde(default)]
+    pub headers: HashMap<String, Secret>,
+    /// Channel for the trades and bot messages of this backend, the trades channel when empty
+    #[serde(default)]
+    pub trades_channel_id: Option<u64>,
+    /// Channel for outages of this backend, the ops channel when empty
+    #[serde(default)]
+    pub ops_channel_id: Option<u64>,
+}
+
+/// Timeouts and retries of requests to the backend API, read at startup
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BackendRequests {
+    pub timeout_seconds: u64,
+    pub connect_timeout_seconds: u64,
+    /// Extra attempts after a timeout, network error or server error
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for every next one
+    pub retry_delay_ms: u64,
+    pub max_retry_delay_ms: u64,
+    /// Failed requests in a row after which requests are paused
+    pub circuit_breaker_failures: u32,
+    /// How long requests are paused before the backend is tried again
+    pub circuit_breaker_cooldown_seconds: u64,
+}
+
+impl Default for BackendRequests {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: 10,
+            connect_timeout_seconds: 5,
+            max_retries: 2,
+            retry_delay_ms: 500,
+            max_retry_delay_ms: 5000,
+            circuit_breaker_failures: 5,
+            circuit_breaker_cooldown_seconds: 60,
+        }
+    }
+}
+
+/// When failing backend requests are reported as an outage
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HealthChecks {
+    /// Failed requests in a row before an endpoint is reported as down
+    pub failure_threshold: u32,
+}
+
+impl Default for HealthChecks {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+        }
+    }
+}
+
+/// Messages for bots that start, stop or change status
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BotLifecycle {
+    pub enabled: bool,
+    /// Channel for these messages, the trades channel of each server when empty
+    pub channel_id: Option<u64>,
+}
+
+impl Default for BotLifecycle {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            channel_id: None,
+        }
+    }
+}
+
+/// Who may start and stop bots from Discord, besides those with the `controlBots` permission
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BotControl {
+    pub allowed_role_ids: Vec<u64>,
+    pub allowed_user_ids: Vec<u64>,
+    /// How long the confirm button can be clicked
+    pub confirmation_timeout_seconds: u64,
+}
+
+impl Default for BotControl {
+    fn default() -> Self {
+        Self {
+            allowed_role_ids: vec![],
+            allowed_user_ids: vec![],
+            confirmation_timeout_seconds: 60,
+        }
+    }
+}
+
+impl BotControl {
+    pub fn allows(&self, user_id: u64, role_ids: &[u64]) -> bool {
+        self.allowed_user_ids.contains(&user_id)
+            || role_ids.iter().any(|r| self.allowed_role_ids.contains(r))
+    }
+}
+
+/// Names shown instead of the names from the backend, matched ignoring case
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DisplayNames {
+    pub accounts: HashMap<String, String>,
+    pub exchanges: HashMap<String, String>,
+    /// By full container name or short name
+    pub bots: HashMap<String, String>,
+    /// Regexes removed from account, exchange and bot names that have no alias,
+    /// e.g. `_account$`
+    pub strip_patterns: Vec<String>,
+}
+
+/// Percentages instead of amounts and balances, for public channels
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Privacy {
+    /// For every channel
+    pub enabled: bool,
+    /// Channels that are private while `enabled` is off
+    pub channel_ids: Vec<u64>,
+}
+
+impl Privacy {
+    pub fn applies_to(&self, channel: ChannelId) -> bool {
+        self.enabled || self.channel_ids.contains(&channel.get())
+    }
+}
+
+/// What a group of commands lets someone do
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Capability {
+    /// Balances, charts and stats of the accounts
+    ViewBalances,
+    /// Trades and the state of the bots and backend
+    ViewTrades,
+    /// Settings, mutes and cash flows
+    ManageConfig,
+    /// Starting and stopping bots
+    ControlBots,
+}
+
+/// Which Discord roles and users may use which commands
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Permissions {
+    /// Everyone may use all commands except `/bot` while disabled
+    pub enabled: bool,
+    /// Capabilities of every member
+    pub everyone: Vec<Capability>,
+    /// Capabilities by role id
+    pub roles: HashMap<u64, Vec<Capability>>,
+    /// Capabilities by user id
+    pub users: HashMap<u64, Vec<Capability>>,
+}
+
+impl Permissions {
+    pub fn grants(&self, user_id: u64, role_ids: &[u64], capability: Capability) -> bool {
+        let has = |list: Option<&Vec<Capability>>| list.is_some_and(|l| l.contains(&capability));
+        self.everyone.contains(&capability)
+            || has(self.users.get(&user_id))
+            || role_ids.iter().any(|r| has(self.roles.get(r)))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertMetric {
+    /// Global PnL % of each controller, as reported by the backend
+    GlobalPnlPct,
+    /// Inventory imbalance of each controller, as reported by the backend
+    InventoryImbalance,
+    /// Fall in percent from the highest account balance within `windowMinutes`,
+    /// leaving deposits and withdrawals out
+    BalanceDropPct,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AlertSeverity {
+    pub fn color(&self) -> u32 {
+        match self {
+            AlertSeverity::Info => 0x3498db,
+            AlertSeverity::Warning => 0xf1c40f,
+            AlertSeverity::Critical => 0xd32121,
+        }
+    }
+}
+
+/// Posts a message when a metric crosses a threshold, and again when it recovers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: AlertMetric,
+    /// Only check this bot, by short or full name
+    #[serde(default)]
+    pub bot: Option<String>,
+    /// Only check this account, for balance rules
+    #[serde(default)]
+    pub account: Option<String>,
+    /// Only check the bots or accounts of this backend, by name
+    #[serde(default)]
+    pub backend: Option<String>,
+    #[serde(default = "AlertRule::default_window_minutes")]
+    pub window_minutes: u64,
+    #[serde(default)]
+    pub below: Option<Decimal>,
+    #[serde(default)]
+    pub above: Option<Decimal>,
+    /// Time before a still failing condition is reported again
+    #[serde(default = "AlertRule::default_cooldown_minutes")]
+    pub cooldown_minutes: u64,
+    #[serde(default = "AlertRule::default_severity")]
+    pub severity: AlertSeverity,
+    /// Channel for the alert, the trades channel when empty
+    #[serde(default)]
+    pub channel_id: Option<u64>,
+    /// Roles mentioned in the alert, by id
+    #[serde(default)]
+    pub mention_roles: Vec<u64>,
+}
+
+impl AlertRule {
+    fn default_window_minutes() -> u64 {
+        60
+    }
+
+    fn default_cooldown_minutes() -> u64 {
+        60
+    }
+
+    fn default_severity() -> AlertSeverity {
+        AlertSeverity::Warning
+    }
+
+    pub fn applies_to(&self, backend: &str) -> bool {
+        self.backend
+            .as_ref()
+            .is_none_or(|name| name.eq_ignore_ascii_case(backend))
+    }
+
+    /// Whether `value` is past one of the thresholds
+    pub fn is_triggered(&self, value: Decimal) -> bool {
+        self.below.is_some_and(|below| value < below)
+            || self.above.is_some_and(|above| value > above)
+    }
+}
+
+/// Quote currencies that are shown with cents unless configured otherwise
+const CENT_CURRENCIES: &[&str] = &[
+    "USD", "USDT", "USDC", "BUSD", "FDUSD", "TUSD", "DAI", "EUR", "GBP",
+];
+
+/// How amounts, prices and balances are rounded and shortened
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NumberFormatting {
+    /// Decimals per asset, e.g. `BTC: 6`, overriding the defaults for quote currencies
+    pub decimals: HashMap<String, u32>,
+    /// Decimals for assets without their own setting, trailing zeros are left out
+    pub default_decimals: u32,
+    /// Significant digits kept for values below one, like prices of small-cap tokens
+    pub significant_digits: u32,
+    /// Values from this size on are shortened to 1.2k, 3.4M, ...; 0 turns it off
+    pub abbreviate_from: Decimal,
+}
+
+impl Default for NumberFormatting {
+    fn default() -> Self {
+        Self {
+            decimals: HashMap::new(),
+            default_decimals: 8,
+            significant_digits: 4,
+            abbreviate_from: Decimal::from(1_000_000),
+        }
+    }
+}
+
+impl NumberFormatting {
+    /// Decimals for `asset`, and whether they are always shown, as with cents
+    pub fn decimals_for(&self, asset: &str) -> (u32, bool) {
+        let configured = self
+            .decimals
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(asset));
+        if let Some((_, decimals)) = configured {
+            return (*decimals, *decimals <= 2);
+        }
+        if CENT_CURRENCIES
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(asset))
+        {
+            return (2, true);
+        }
+        (self.default_decimals, false)
+    }
+
+    pub fn should_abbreviate(&self, value: Decimal) -> bool {
+        !self.abbreviate_from.is_zero() && value.abs() >= self.abbreviate_from
+    }
+
+    fn validate(&self) -> Result<()> {
+        let too_many = self
+            .decimals
+            .values()
+            .chain([&self.default_decimals])
+            .any(|d| *d > number_format::MAX_DECIMALS);
+        if too_many {
+            return Err(anyhow!(
+                "Number formatting allows at most {} decimals",
+                number_format::MAX_DECIMALS
+            ));
+        }
+        if !(1..=number_format::MAX_DECIMALS).contains(&self.significant_digits) {
+            return Err(anyhow!(
+                "significantDigits must be between 1 and {}",
+                number_format::MAX_DECIMALS
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Also posts the trades matching the filters to another channel
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeRoute {
+    pub channel_id: u64,
+    /// Short or full bot names, any bot when empty
+    #[serde(default)]
+    pub bots: Vec<String>,
+    /// Pairs like `BTC-USDT`, any pair when empty
+    #[serde(default)]
+    pub pairs: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+impl TradeRoute {
+    pub fn matches(&self, bot_name: &str, trade: &Trade<'_>) -> bool {
+        let pair = format!("{}-{}", trade.base_asset, trade.quote_asset);
+        (self.bots.is_empty() || self.bots.iter().any(|b| matches_bot_name(b, bot_name)))
+            && (self.pairs.is_empty()
+                || self
+                    .pairs
+                    .iter()
+                    .any(|p| p.replace('/', "-").eq_ignore_ascii_case(&pair)))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase", default)]
 pub struct Config<'c> {
     pub bot_token: Cow<'c, str>,
     pub stats_channel_id: u64,
     pub backend_api_base_url: Url,
     pub cache_path: PathBuf,
+    /// Shows bots by the name they were deployed with instead of their container name,
+    /// e.g. `pmm` for `hummingbot-pmm-2024.10.02_20.05`
     pub cache_strip_bot_names: bool,
+    pub display_names: DisplayNames,
+    /// Regexes that read bot names, with a `name` group and optional `version` and
+    /// `deployed` groups. Only read at startup, changing them at runtime is refused. Bots
+    /// that match none keep their full name.
+    pub bot_name_patterns: Vec<String>,
     pub scheduled_chart_announcement: ScheduledStats<'c>,
+    pub chart: ChartOptions,
+    pub reporting_currency: ReportingCurrency,
+    pub cash_flow_detection: CashFlowDetection,
+    /// Custom trade embed, the built-in one in the configured language when empty
+    pub trade_embed: Option<TradeEmbedTemplate>,
+    /// Language of the bot's messages, one of the catalogs in `resources/locales`
+    pub locale: Cow<'c, str>,
+    /// Timezone dates are shown in, e.g. `Europe/Amsterdam`
+    pub timezone: Cow<'c, str>,
+    /// Language per Discord server, by guild id
+    pub guild_locales: HashMap<u64, String>,
+    pub number_formatting: NumberFormatting,
+    /// Bots whose trades aren't posted, by short or full name
+    pub muted_bots: Vec<String>,
+    pub trade_routes: Vec<TradeRoute>,
+    pub rate_limit: RateLimit,
+    pub alerts: Vec<AlertRule>,
+    pub bot_lifecycle: BotLifecycle,
+    /// Channel for messages about the bot itself, like backend outages, the trades
+    /// channel when empty
+    pub ops_channel_id: Option<u64>,
+    pub health_checks: HealthChecks,
+    pub backend_requests: BackendRequests,
+    pub bot_control: BotControl,
+    pub permissions: Permissions,
+    pub privacy: Privacy,
+    /// Login for the backend API, from the `BACKEND_API_USERNAME` and
+    /// `BACKEND_API_PASSWORD` environment variables (or their `_FILE` variants) when empty
+    pub backend_auth: Option<BackendAuth>,
+    /// Extra headers sent with every backend request, e.g. an API key
+    pub backend_headers: HashMap<String, Secret>,
+    /// Several backends, each with its own login and channels, instead of
+    /// `backendApiBaseUrl`. Read at startup, their balances are cached in a folder per name.
+    pub backends: Vec<BackendConfig>,
 }
 
 impl<'c> Default for Config<'c> {
@@ -30,13 +700,336 @@ impl<'c> Default for Config<'c> {
             stats_channel_id: 39923329,
             cache_path: PathBuf::from("/storage/mdh_discord/cache"),
             cache_strip_bot_names: true,
+            display_names: DisplayNames::default(),
+            bot_name_patterns: vec![DEFAULT_BOT_NAME_PATTERN.to_string()],
             backend_api_base_url: Url::parse("http://backend-api:8000").unwrap(),
             scheduled_chart_announcement: ScheduledStats {
                 message: "Good morning! Here are the scheduled profits (or losses) from yesterdays operation 💸".into(),
                 schedule: "0 0 9 * * *".into(),
                 enabled: false,
                 channel_id: 29384550,
+                include_stats: false,
+                stats_period_days: None,
             },
+            chart: ChartOptions::default(),
+            reporting_currency: ReportingCurrency::default(),
+            cash_flow_detection: CashFlowDetection::default(),
+            trade_embed: None,
+            locale: DEFAULT_LOCALE.into(),
+            timezone: "UTC".into(),
+            guild_locales: HashMap::new(),
+            number_formatting: NumberFormatting::default(),
+            muted_bots: vec![],
+            trade_routes: vec![],
+            rate_limit: RateLimit::default(),
+            alerts: vec![],
+            bot_lifecycle: BotLifecycle::default(),
+            ops_channel_id: None,
+            health_checks: HealthChecks::default(),
+            backend_requests: BackendRequests::default(),
+            bot_control: BotControl::default(),
+            permissions: Permissions::default(),
+            privacy: Privacy::default(),
+            backend_auth: None,
+            backend_headers: HashMap::new(),
+            backends: vec![],
+        }
+    }
+}
+
+impl<'c> Config<'c> {
+    pub fn ops_channel(&self) -> ChannelId {
+        ChannelId::new(self.ops_channel_id.unwrap_or(self.stats_channel_id))
+    }
+
+    /// Whether a member with these roles may use the commands of `capability`.
+    /// Administrators may do everything but control bots, which takes `botControl` or
+    /// a grant. Without `permissions` everyone may view, only administrators manage.
+    pub fn allows(
+        &self,
+        user_id: u64,
+        role_ids: &[u64],
+        administrator: bool,
+        capability: Capability,
+    ) -> bool {
+        match capability {
+            Capability::ControlBots => {
+                self.bot_control.allows(user_id, role_ids)
+                    || (self.permissions.enabled
+                        && self.permissions.grants(user_id, role_ids, capability))
+            }
+            _ if administrator => true,
+            Capability::ManageConfig if !self.permissions.enabled => false,
+            _ if !self.permissions.enabled => true,
+            _ => self.permissions.grants(user_id, role_ids, capability),
+        }
+    }
+
+    pub fn naming(&self) -> Naming {
+        Naming::new(&self.display_names, self.cache_strip_bot_names)
+    }
+
+    /// The ops channel of a backend, the general one when it has none
+    pub fn backend_ops_channel(&self, backend: &str) -> ChannelId {
+        self.backend(backend)
+            .and_then(|b| b.ops_channel_id)
+            .map_or(self.ops_channel(), ChannelId::new)
+    }
+
+    /// Checks the parts serde can't, like templates and colors
+    pub fn validate(&self) -> Result<()> {
+        let schedule = &self.scheduled_chart_announcement.schedule;
+        Job::new(schedule.as_ref(), |_, _| {})
+            .map_err(|e| anyhow!("Invalid schedule \"{}\": {}", schedule, e))?;
+        Template::validate(
+            &self.scheduled_chart_announcement.message,
+            ANNOUNCEMENT_TEMPLATE_VARIABLES,
+        )?;
+        if let Some(embed) = &self.trade_embed {
+            Template::validate(&embed.title, TRADE_TEMPLATE_VARIABLES)?;
+            Template::validate(&embed.description, TRADE_TEMPLATE_VARIABLES)?;
+            if embed.fields.len() > 25 {
+                return Err(anyhow!("Trade embeds can have at most 25 fields"));
+            }
+            for field in embed.fields.iter() {
+                Template::validate(&field.name, TRADE_TEMPLATE_VARIABLES)?;
+                Template::validate(&field.value, TRADE_TEMPLATE_VARIABLES)?;
+            }
+            parse_color(&embed.buy_color)?;
+            parse_color(&embed.sell_color)?;
+        }
+        parse_timezone(&self.timezone)?;
+        BotNameParser::new(&self.bot_name_patterns)?;
+        for pattern in self.display_names.strip_patterns.iter() {
+            Regex::new(pattern)
+                .map_err(|e| anyhow!("Invalid strip pattern \"{}\": {}", pattern, e))?;
+        }
+        self.number_formatting.validate()?;
+        for (i, rule) in self.alerts.iter().enumerate() {
+            if rule.below.is_none() && rule.above.is_none() {
+                return Err(anyhow!("Alert \"{}\" needs `below` or `above`", rule.name));
+            }
+            if self.alerts[..i].iter().any(|r| r.name == rule.name) {
+                return Err(anyhow!("Alert name \"{}\" is used twice", rule.name));
+            }
+        }
+        if self.rate_limit.enabled
+            && (self.rate_limit.max_messages == 0 || self.rate_limit.window_seconds == 0)
+        {
+            return Err(anyhow!(
+                "rateLimit needs at least one message per window of at least a second"
+            ));
+        }
+        for (i, backend) in self.backends.iter().enumerate() {
+            if backend.name.trim().is_empty() {
+                return Err(anyhow!("Every backend needs a name"));
+            }
+            if self.backends[..i]
+                .iter()
+                .any(|b| b.name.eq_ignore_ascii_case(&backend.name))
+            {
+                return Err(anyhow!("Backend name \"{}\" is used twice", backend.name));
+            }
+        }
+        for rule in self.alerts.iter() {
+            if let Some(name) = &rule.backend {
+                if self.backend(name).is_none() {
+                    return Err(anyhow!(
+                        "Alert \"{}\" uses unknown backend \"{}\"",
+                        rule.name,
+                        name
+                    ));
+                }
+            }
+        }
+        let requests = &self.backend_requests;
+        if requests.timeout_seconds == 0 || requests.connect_timeout_seconds == 0 {
+            return Err(anyhow!(
+                "backendRequests timeouts must be at least a second"
+            ));
+        }
+        if requests.circuit_breaker_failures == 0 {
+            return Err(anyhow!(
+                "backendRequests.circuitBreakerFailures must be at least 1"
+            ));
+        }
+        if self.bot_control.confirmation_timeout_seconds == 0 {
+            return Err(anyhow!(
+                "botControl.confirmationTimeoutSeconds must be at least 1"
+            ));
+        }
+        if self.health_checks.failure_threshold == 0 {
+            return Err(anyhow!("healthChecks.failureThreshold must be at least 1"));
+        }
+        for locale in std::iter::once(self.locale.as_ref())
+            .chain(self.guild_locales.values().map(String::as_str))
+        {
+            if !i18n::is_supported(locale) {
+                return Err(anyhow!(
+                    "Unsupported locale \"{}\", available: {}",
+                    locale,
+                    i18n::supported_locales().join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The configured backends, or the one from `backendApiBaseUrl` when there are none
+    pub fn backend_configs(&self) -> Vec<BackendConfig> {
+        if !self.backends.is_empty() {
+            return self.backends.clone();
+        }
+        vec![BackendConfig {
+            name: DEFAULT_BACKEND.to_string(),
+            base_url: self.backend_api_base_url.clone(),
+            auth: self.backend_auth.clone(),
+            headers: self.backend_headers.clone(),
+            trades_channel_id: None,
+            ops_channel_id: None,
+        }]
+    }
+
+    pub fn backend(&self, name: &str) -> Option<BackendConfig> {
+        self.backend_configs()
+            .into_iter()
+            .find(|b| b.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn locales(&self) -> Locales {
+        Locales {
+            default: self.locale.to_string(),
+            timezone: parse_timezone(&self.timezone).unwrap_or_default(),
+            guilds: self.guild_locales.clone(),
+            numbers: Arc::new(self.number_formatting.clone()),
+        }
+    }
+}
+
+/// The config, shared with the background tasks so `/config` changes apply right away.
+/// The changes are kept in a state file that overrides config.yml, so the config file
+/// stays as it was written.
+pub struct ConfigStore<'c> {
+    overrides_path: PathBuf,
+    /// The config as loaded from config.yml, what the overrides are compared to
+    base: serde_yaml::Value,
+    config: RwLock<Config<'c>>,
+    updating: tokio::sync::Mutex<()>,
+    changes: watch::Sender<u64>,
+}
+
+impl<'c> ConfigStore<'c> {
+    /// Applies the overrides saved at `overrides_path` to `config`
+    pub fn new(overrides_path: PathBuf, config: Config<'c>) -> Result<ConfigStore<'c>> {
+        let base = serde_yaml::to_value(&config)?;
+        let config = if overrides_path.exists() {
+            let overrides: serde_yaml::Value =
+                serde_yaml::from_str(&fs::read_to_string(&overrides_path)?)?;
+            let mut merged = base.clone();
+            merge_yaml(&mut merged, overrides);
+            let config: Config = serde_yaml::from_value(merged)
+                .map_err(|e| anyhow!("Invalid overrides in {}: {}", overrides_path.display(), e))?;
+            config.validate()?;
+            config
+        } else {
+            config
+        };
+        Ok(ConfigStore {
+            overrides_path,
+            base,
+            config: RwLock::new(config),
+            updating: tokio::sync::Mutex::new(()),
+            changes: watch::channel(0).0,
+        })
+    }
+
+    /// A copy of the current config
+    pub fn get(&self) -> Config<'c> {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Applies `change`, checks the result and saves how it differs from config.yml
+    pub async fn update(&self, change: impl FnOnce(&mut Config<'c>)) -> Result<Config<'c>> {
+        // One update at a time, so none overwrites the saved overrides of another
+        let _updating = self.updating.lock().await;
+        let mut updated = self.get();
+        change(&mut updated);
+        updated.validate()?;
+        // The bot name parser is set once at startup
+        if updated.bot_name_patterns != self.config.read().unwrap().bot_name_patterns {
+            return Err(anyhow!(
+                "botNamePatterns can only be changed in config.yml, restart to apply them"
+            ));
+        }
+        let overrides = diff_yaml(&self.base, &serde_yaml::to_value(&updated)?)
+            .unwrap_or(serde_yaml::Value::Mapping(Default::default()));
+        let contents = serde_yaml::to_string(&overrides)?;
+        let path = self.overrides_path.clone();
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            let tmp_path = path.with_extension("yml.tmp");
+            fs::write(&tmp_path, contents)?;
+            fs::rename(&tmp_path, &path)?;
+            Ok(())
+        })
+        .await??;
+        *self.config.write().unwrap() = updated.clone();
+        self.changes.send_modify(|version| *version += 1);
+        Ok(updated)
+    }
+
+    /// Notifies about every change made through `update`
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.changes.subscribe()
+    }
+}
+
+/// Tag of mappings in the overrides that replace the one in config.yml as a whole
+const REPLACE_TAG: &str = "!replace";
+
+/// The parts of `updated` that differ from `base`, mappings compared key by key. A
+/// mapping that lost keys is tagged to replace the one in config.yml as a whole.
+fn diff_yaml(base: &serde_yaml::Value, updated: &serde_yaml::Value) -> Option<serde_yaml::Value> {
+    use serde_yaml::value::{Tag, TaggedValue};
+    use serde_yaml::Value;
+    match (base, updated) {
+        (Value::Mapping(base_map), Value::Mapping(updated_map)) => {
+            if !base_map.keys().all(|key| updated_map.contains_key(key)) {
+                return Some(Value::Tagged(Box::new(TaggedValue {
+                    tag: Tag::new(REPLACE_TAG),
+                    value: updated.clone(),
+                })));
+            }
+            let diff: serde_yaml::Mapping = updated_map
+                .iter()
+                .filter_map(|(key, value)| {
+                    let changed = match base_map.get(key) {
+                        Some(base_value) => diff_yaml(base_value, value),
+                        None => Some(value.clone()),
+                    };
+                    changed.map(|value| (key.clone(), value))
+                })
+                .collect();
+            (!diff.is_empty()).then_some(Value::Mapping(diff))
+        }
+        _ => (base != updated).then(|| updated.clone()),
+    }
+}
+
+/// Writes `overrides` over `base`, merging mappings key by key
+fn merge_yaml(base: &mut serde_yaml::Value, overrides: serde_yaml::Value) {
+    use serde_yaml::Value;
+    match (base, overrides) {
+        (Value::Mapping(base), Value::Mapping(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_yaml(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
         }
+        (base, Value::Tagged(tagged)) if tagged.tag == REPLACE_TAG => *base = tagged.value,
+        (base, overrides) => *base = overrides,
     }
 }
@@ -0,0 +1,223 @@
+use anyhow::{anyhow, Result};
+use chrono::TimeZone;
+use chrono_tz::Tz;
+use lazy_static::lazy_static;
+use log::warn;
+use poise::serenity_prelude::GuildId;
+use rust_decimal::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::config::NumberFormatting;
+use crate::utils::number_format;
+use crate::utils::template;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NumberFormat {
+    decimal: String,
+    group: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Catalog {
+    number: NumberFormat,
+    date_format: String,
+    /// Name of the locale as known by chrono, for month and day names
+    chrono_locale: String,
+    messages: HashMap<String, String>,
+}
+
+lazy_static! {
+    static ref CATALOGS: HashMap<&'static str, Catalog> = [
+        ("en", include_str!("../resources/locales/en.yml")),
+        ("nl", include_str!("../resources/locales/nl.yml")),
+        ("de", include_str!("../resources/locales/de.yml")),
+    ]
+    .into_iter()
+    .map(|(code, yaml)| (code, serde_yaml::from_str(yaml).unwrap()))
+    .collect();
+}
+
+pub fn is_supported(locale: &str) -> bool {
+    CATALOGS.contains_key(locale)
+}
+
+pub fn supported_locales() -> Vec<&'static str> {
+    let mut locales: Vec<&'static str> = CATALOGS.keys().copied().collect();
+    locales.sort_unstable();
+    locales
+}
+
+pub fn parse_timezone(timezone: &str) -> Result<Tz> {
+    timezone
+        .parse()
+        .map_err(|e| anyhow!("Invalid timezone \"{}\": {}", timezone, e))
+}
+
+/// Picks the locale per guild, falling back to the default one
+#[derive(Clone)]
+pub struct Locales {
+    pub default: String,
+    pub timezone: Tz,
+    pub guilds: HashMap<u64, String>,
+    pub numbers: Arc<NumberFormatting>,
+}
+
+impl Locales {
+    pub fn for_guild(&self, guild_id: Option<GuildId>) -> Locale {
+        let code = guild_id
+            .and_then(|id| self.guilds.get(&id.get()))
+            .unwrap_or(&self.default);
+        Locale {
+            numbers: self.numbers.clone(),
+            ..Locale::new(code, self.timezone)
+        }
+    }
+}
+
+/// Translations and number/date formatting for one language and timezone
+#[derive(Clone)]
+pub struct Locale {
+    code: &'static str,
+    timezone: Tz,
+    numbers: Arc<NumberFormatting>,
+}
+
+impl Locale {
+    /// Unknown locales fall back to English
+    pub fn new(code: &str, timezone: Tz) -> Locale {
+        let code = CATALOGS
+            .get_key_value(code)
+            .map_or(DEFAULT_LOCALE, |(code, _)| *code);
+        Locale {
+            code,
+            timezone,
+            numbers: Arc::default(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    pub fn numbers(&self) -> &NumberFormatting {
+        &self.numbers
+    }
+
+    fn catalog(&self) -> &'static Catalog {
+        &CATALOGS[self.code]
+    }
+
+    /// Translated text for `key`, using English for missing translations
+    pub fn t(&self, key: &str) -> String {
+        self.catalog()
+            .messages
+            .get(key)
+            .or_else(|| CATALOGS[DEFAULT_LOCALE].messages.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// Translated text with its `{variable}` placeholders filled in
+    pub fn tf(&self, key: &str, variables: &[(&str, String)]) -> String {
+        let variables: HashMap<&str, String> = variables.iter().cloned().collect();
+        let text = self.t(key);
+        template::render(&text, &variables).unwrap_or_else(|e| {
+            warn!("Invalid translation of {} in {}: {}", key, self.code, e);
+            text
+        })
+    }
+
+    /// Rounds to `dp` decimals and adds the locale's separators
+    pub fn format_decimal(&self, value: Decimal, dp: u32) -> String {
+        let number = &self.catalog().number;
+        let rounded = value.round_dp_with_strategy(dp, RoundingStrategy::MidpointAwayFromZero);
+        let digits = format!("{:.*}", dp as usize, rounded.abs());
+        let (integer, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+        let mut grouped = String::new();
+        for (i, c) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push_str(&number.group);
+            }
+            grouped.push(c);
+        }
+        let sign = if rounded.is_sign_negative() && !rounded.is_zero() {
+            "-"
+        } else {
+            ""
+        };
+        if fraction.is_empty() {
+            format!("{}{}", sign, grouped)
+        } else {
+            format!("{}{}{}{}", sign, grouped, number.decimal, fraction)
+        }
+    }
+
+    /// Shows a decimal as it is, without trailing zeros
+    pub fn format_normalized(&self, value: Decimal) -> String {
+        let normalized = value.normalize();
+        self.format_decimal(normalized, normalized.scale())
+    }
+
+    /// Formats an amount, price or balance in `asset` with the asset's precision,
+    /// shortening large values to 1.2k, 3.4M, ...
+    pub fn format_amount(&self, value: Decimal, asset: &str) -> String {
+        if self.numbers.should_abbreviate(value) {
+            return self.format_compact(value);
+        }
+        let (decimals, fixed) = self.numbers.decimals_for(asset);
+        let scale = number_format::display_scale(value, decimals, self.numbers.significant_digits);
+        let min_decimals = if fixed { decimals } else { 0 };
+        let rounded = number_format::round_trimmed(value, scale, min_decimals);
+        self.format_decimal(rounded, rounded.scale())
+    }
+
+    /// Formats a value shortened to 1.2k, 3.4M, ..., as far as it is large enough
+    pub fn format_compact(&self, value: Decimal) -> String {
+        match number_format::abbreviate(value) {
+            Some((short, suffix)) => format!("{}{}", self.format_normalized(short), suffix),
+            None => self.format_decimal(value, 2),
+        }
+    }
+
+    /// Formats a fraction as signed percentage, e.g. `0.0123` as `+1.23%`
+    pub fn format_pct(&self, fraction: f64) -> String {
+        let value = Decimal::from_f64(fraction * 100.0).unwrap_or_default();
+        let sign = if value.round_dp(2).is_sign_positive() {
+            "+"
+        } else {
+            ""
+        };
+        format!("{}{}%", sign, self.format_decimal(value, 2))
+    }
+
+    /// Formats a fraction as percentage without sign, e.g. `0.0123` as `1.23%`
+    pub fn format_share(&self, fraction: Decimal) -> String {
+        format!(
+            "{}%",
+            self.format_decimal(fraction * Decimal::ONE_HUNDRED, 2)
+        )
+    }
+
+    pub fn format_date(&self, timestamp: u64) -> String {
+        let catalog = self.catalog();
+        let Some(date) = self.timezone.timestamp_opt(timestamp as i64, 0).single() else {
+            return timestamp.to_string();
+        };
+        match chrono::Locale::try_from(catalog.chrono_locale.as_str()) {
+            Ok(locale) => date
+                .format_localized(&catalog.date_format, locale)
+                .to_string(),
+            Err(_) => date.format(&catalog.date_format).to_string(),
+        }
+    }
+}
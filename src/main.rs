@@ -1,6 +1,8 @@
 mod args;
 mod backend_api;
+mod commands;
 mod config;
+mod i18n;
 mod structs;
 #[cfg(test)]
 mod tests;
@@ -11,300 +13,1480 @@ use crate::structs::trade::Trade;
 use anyhow::anyhow;
 use anyhow::Result;
 use args::Args;
-use backend_api::client::BackendAPIClient;
+use backend_api::error::BackendError;
 use clap::Parser;
+use commands::permissions::can_manage_config;
+use config::parse_color;
+use config::AlertMetric;
+use config::AlertRule;
+use config::AlertSeverity;
 use config::Config;
+use config::ConfigStore;
+use config::TradeEmbedTemplate;
+use i18n::Locale;
+use i18n::Locales;
 use log::debug;
 use log::error;
+use log::info;
 use log::warn;
 use poise::serenity_prelude as serenity;
 use poise::serenity_prelude::ChannelId;
+use poise::serenity_prelude::CreateAllowedMentions;
 use poise::serenity_prelude::CreateAttachment;
 use poise::serenity_prelude::CreateEmbed;
+use poise::serenity_prelude::CreateEmbedAuthor;
 use poise::serenity_prelude::CreateMessage;
+use poise::serenity_prelude::RoleId;
 use poise::CreateReply;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
+use std::process::ExitCode;
 use std::sync::Arc;
 use std::time::Duration;
-use structs::bot_balance::BotBalance;
-use structs::extensions::converter::AccountStateConverter;
+use structs::alerts::balance_drop_measurements;
+use structs::alerts::bot_measurements;
+use structs::alerts::AlertEngine;
+use structs::alerts::AlertEvent;
+use structs::alerts::AlertEventKind;
+use structs::alerts::BalanceProbes;
+use structs::alerts::Measurement;
+use structs::backends::Backend;
+use structs::backends::Backends;
+use structs::balance_tracker::BalanceSummary;
+use structs::bot_lifecycle::BotLifecycleTracker;
+use structs::bot_lifecycle::BotSnapshot;
+use structs::bot_lifecycle::LifecycleEvent;
+use structs::cash_flow::account_flows;
+use structs::currency_converter::ConversionRate;
 use structs::extensions::converter::BotsConverter;
-use structs::jsonl_cache::JsonCache;
+use structs::guild_settings::GuildSettings;
+use structs::guild_settings::GuildSettingsStore;
+use structs::guild_settings::GuildTarget;
+use structs::health::HealthEvent;
+use structs::mutes::MuteStore;
+use structs::naming::Naming;
+use structs::performance_stats::account_series;
+use structs::performance_stats::net_of_flows;
+use structs::performance_stats::DailyReturn;
+use structs::performance_stats::PerformanceStats;
+use structs::privacy::index_series;
+use structs::privacy::notional_share;
+use structs::privacy::share;
+use structs::privacy::AccountMasks;
 use structs::profit_chart::ChartData;
 use structs::profit_chart::ChartDataEntry;
+use structs::profit_chart::ChartLocale;
+use structs::profit_chart::ChartOptions;
+use structs::supervisor::Shutdown;
+use structs::supervisor::Supervisor;
+use structs::supervisor::TaskRestart;
 use structs::trade::TradeSide;
+use structs::trade_digest::TradeDigest;
+use structs::trade_digest::{new_trades, TradeThrottle};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::time::sleep_until;
 use tokio::time::Instant;
 use tokio_cron_scheduler::{Job, JobScheduler};
-use utils::extract_bot_name::extract_bot_name;
+use utils::duration::format_duration;
+use utils::extract_bot_name::matches_bot_name;
+use utils::extract_bot_name::set_bot_name_patterns;
+use utils::template;
+use utils::truncate::truncate;
+use utils::unix_timestamp::unix_timestamp;
 
 struct Data<'c> {
-    config: Config<'c>,
-    client: Arc<BackendAPIClient>,
-    cache: Arc<JsonCache<BotBalance>>,
+    config: Arc<ConfigStore<'c>>,
+    backends: Arc<Backends>,
+    guilds: Arc<GuildSettingsStore>,
+    mutes: Arc<MuteStore>,
 } // User data, which is stored and accessible in all command invocations
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'c, 'a> = poise::Context<'a, Data<'c>, Error>;
 
-fn make_chart(cache: &JsonCache<BotBalance>) -> Result<Vec<u8>> {
+fn ctx_locale(ctx: Context<'_, '_>) -> Locale {
+    ctx.data().config.get().locales().for_guild(ctx.guild_id())
+}
+
+async fn autocomplete_backend(ctx: Context<'_, '_>, partial: &str) -> Vec<String> {
+    ctx.data()
+        .backends
+        .names()
+        .into_iter()
+        .filter(|name| name.to_lowercase().starts_with(&partial.to_lowercase()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Backends a command is limited to, `None` after telling the user the name is unknown
+async fn select_backends<'a>(
+    ctx: Context<'_, 'a>,
+    locale: &Locale,
+    name: Option<&str>,
+) -> Result<Option<Vec<&'a Backend>>, Error> {
+    match ctx.data().backends.select(name) {
+        Ok(backends) => Ok(Some(backends)),
+        Err(_) => {
+            let names = ctx.data().backends.names().join(", ");
+            let builder = CreateReply::default().ephemeral(true).content(locale.tf(
+                "backend.unknown",
+                &[
+                    ("backend", name.unwrap_or_default().to_string()),
+                    ("backends", names),
+                ],
+            ));
+            ctx.send(builder).await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Updates the balance cache of each backend, with the rate to convert its balances.
+/// Backends that can't be reached use the rate of their latest cached balance.
+async fn update_balances(
+    backends: Vec<&Backend>,
+    skip_unchanged: bool,
+) -> Vec<(&Backend, ConversionRate)> {
+    let mut rates = vec![];
+    for backend in backends {
+        let rate = match backend.balances.update(skip_unchanged).await {
+            Ok(rate) => rate,
+            Err(e) => {
+                log_backend_error(&format!("updating balance cache of {}", backend.name), &e);
+                backend.balances.cached_rate()
+            }
+        };
+        rates.push((backend, rate));
+    }
+    rates
+}
+
+/// Rate shown in notes, all backends convert to the same reporting currency
+fn shown_rate(balances: &[(&Backend, ConversionRate)]) -> ConversionRate {
+    balances
+        .first()
+        .map(|(_, rate)| rate.clone())
+        .unwrap_or_else(|| ConversionRate::identity(""))
+}
+
+/// Totals over the visible accounts of all `backends`
+fn balance_summary(
+    backends: &[&Backend],
+    settings: &GuildSettings,
+    naming: &Naming,
+) -> Result<Option<BalanceSummary>> {
+    let mut combined: Option<BalanceSummary> = None;
+    for backend in backends {
+        let Some(summary) = backend
+            .balances
+            .summary(|account| settings.shows_account(account, naming))?
+        else {
+            continue;
+        };
+        match combined.as_mut() {
+            Some(combined) => {
+                combined.total += summary.total;
+                combined.pnl_24h += summary.pnl_24h;
+            }
+            None => combined = Some(summary),
+        }
+    }
+    Ok(combined)
+}
+
+/// Performance of the visible accounts of all `balances`, by the name they're shown with
+fn account_stats(
+    backends: &Backends,
+    balances: &[(&Backend, ConversionRate)],
+    days: Option<u32>,
+    settings: &GuildSettings,
+    labels: &AccountLabels,
+) -> Result<BTreeMap<String, PerformanceStats>> {
+    let mut stats = BTreeMap::new();
+    for (backend, rate) in balances {
+        let per_account = PerformanceStats::per_account(
+            &backend.balances.get_balances()?,
+            &backend.balances.get_cash_flows()?,
+            rate,
+            days,
+        );
+        for (account, account_stats) in per_account {
+            if settings.shows_account(&account, &labels.naming) {
+                stats.insert(labels.label(backends, backend, &account), account_stats);
+            }
+        }
+    }
+    Ok(stats)
+}
+
+/// Stand-ins for the account names of all backends in private channels, numbered the
+/// same whichever backends or accounts a message shows
+fn account_masks(backends: &Backends) -> Result<AccountMasks> {
+    let mut names = vec![];
+    for backend in backends.all() {
+        for balance in backend.balances.get_balances()? {
+            names.extend(
+                balance
+                    .accounts
+                    .keys()
+                    .map(|account| backends.tag(backend, account)),
+            );
+        }
+    }
+    Ok(AccountMasks::new(names))
+}
+
+/// How accounts are named in a message: by their alias, or by a number in private channels
+struct AccountLabels<'a> {
+    naming: Naming,
+    masks: Option<AccountMasks>,
+    locale: &'a Locale,
+}
+
+impl<'a> AccountLabels<'a> {
+    fn new(
+        config: &Config<'_>,
+        backends: &Backends,
+        locale: &'a Locale,
+        private: bool,
+    ) -> Result<AccountLabels<'a>> {
+        Ok(AccountLabels {
+            naming: config.naming(),
+            masks: private.then(|| account_masks(backends)).transpose()?,
+            locale,
+        })
+    }
+
+    fn is_private(&self) -> bool {
+        self.masks.is_some()
+    }
+
+    fn label(&self, backends: &Backends, backend: &Backend, account: &str) -> String {
+        match &self.masks {
+            Some(masks) => match masks.number(&backends.tag(backend, account)) {
+                Some(number) => self
+                    .locale
+                    .tf("privacy.account", &[("number", number.to_string())]),
+                None => self.locale.t("privacy.hidden"),
+            },
+            None => backends.tag(backend, &self.naming.account(account)),
+        }
+    }
+}
+
+fn with_rate_note(message: &str, rate: &ConversionRate, locale: &Locale) -> String {
+    match rate.note(locale) {
+        Some(note) => format!("{}\n-# {}", message, note),
+        None => message.to_string(),
+    }
+}
+
+fn make_chart(
+    backends: &Backends,
+    balances: &[(&Backend, ConversionRate)],
+    options: &ChartOptions,
+    locale: &Locale,
+    settings: &GuildSettings,
+    labels: &AccountLabels,
+) -> Result<Vec<u8>> {
+    let rate = &shown_rate(balances);
     let mut chart_data = ChartData {
+        options: options.clone(),
+        locale: ChartLocale {
+            code: locale.code().to_string(),
+            timezone: locale.timezone().name().to_string(),
+            balance: locale.t("chart.balance"),
+            time: locale.t("chart.time"),
+            decimals: locale.numbers().decimals_for(&rate.to).0,
+            abbreviate_from: locale.numbers().abbreviate_from,
+        },
         ..Default::default()
     };
-    for balance in cache.get_all_objects().unwrap().iter() {
-        let merged_balance = balance.merge_across_exchanges();
-        for (account, entries) in merged_balance.iter() {
-            if chart_data.chart_data.contains_key(account) {
-                let to_add_to = chart_data.chart_data.get_mut(account).unwrap();
-                to_add_to.extend(entries.iter().map(|x| ChartDataEntry {
-                    timestamp: balance.timestamp,
-                    balance: x.amount,
-                }));
-            } else {
-                chart_data.chart_data.insert(
-                    account.clone(),
-                    entries
-                        .iter()
-                        .map(|x| ChartDataEntry {
-                            timestamp: balance.timestamp,
-                            balance: x.amount,
-                        })
-                        .collect(),
-                );
+    if labels.is_private() {
+        // Every line starts at 100, so only the changes can be read from the chart
+        chart_data.locale.balance = locale.t("chart.index");
+        chart_data.locale.decimals = 0;
+        chart_data.options.currency_label = Some(locale.t("chart.index"));
+    }
+    chart_data
+        .options
+        .currency_label
+        .get_or_insert_with(|| rate.to.clone());
+    for (backend, rate) in balances {
+        let tracker = &backend.balances;
+        let flows = account_flows(&tracker.get_cash_flows()?, rate);
+        for (account, series) in account_series(&tracker.get_balances()?, rate) {
+            if !settings.shows_account(&account, &labels.naming) {
+                continue;
             }
+            let series = match flows.get(&account) {
+                Some(flows) if options.net_of_flows => net_of_flows(&series, flows),
+                _ => series,
+            };
+            let series = if labels.is_private() {
+                index_series(&series)
+            } else {
+                series
+            };
+            chart_data.chart_data.insert(
+                labels.label(backends, backend, &account),
+                series
+                    .into_iter()
+                    .map(|(timestamp, balance)| ChartDataEntry { timestamp, balance })
+                    .collect(),
+            );
         }
     }
     chart_data.render_chart()
 }
 
 /// Test the stats announcement
-#[poise::command(
-    slash_command,
-    prefix_command,
-    default_member_permissions = "ADMINISTRATOR"
-)]
+#[poise::command(slash_command, prefix_command, check = "can_manage_config")]
 async fn stats_announcement_test(ctx: Context<'_, '_>) -> Result<(), Error> {
     let builder = CreateReply::default()
         .ephemeral(true)
-        .content("Testing stats announcement... Message should arrive soon");
+        .content(ctx_locale(ctx).t("announcement_test.sending"));
     ctx.send(builder).await?;
     let data = ctx.data();
-    let chart_announcement_channel =
-        ChannelId::new(data.config.scheduled_chart_announcement.channel_id);
-
-    if data.cache.is_empty() {
-        let account_state = data.client.get_account_state().await;
-        match account_state {
-            Ok(account_state) => {
-                let balance_entry = account_state.to_bot_balance();
-                data.cache.write(balance_entry).unwrap();
-            }
-            Err(e) => {
-                warn!("Error (Ignored) getting bots from cache: {}", e);
-            }
-        }
-    }
+    let balances = update_balances(data.backends.all(), true).await;
 
+    // Goes to this server's announcement channel, even when announcements are off
+    let config = data.config.get();
+    let announcement = &config.scheduled_chart_announcement;
+    let settings = data.guilds.get(ctx.guild_id());
+    let target = GuildTarget {
+        guild_id: ctx.guild_id(),
+        channel: ChannelId::new(
+            settings
+                .announcement_channel_id
+                .unwrap_or(announcement.channel_id),
+        ),
+        settings,
+    };
     notify_bot_stats(
         ctx.serenity_context(),
-        &data.config.scheduled_chart_announcement.message,
-        &data.cache,
-        &chart_announcement_channel,
+        &config,
+        &target,
+        &data.backends,
+        &balances,
     )
     .await?;
     Ok(())
 }
 
-/// Displays a profit chart
-#[poise::command(slash_command, prefix_command)]
-async fn profit_chart(ctx: Context<'_, '_>) -> Result<(), Error> {
-    let reply = ctx.reply("Starting to post the charts!").await?;
-    let data = ctx.data();
-
-    let account_state = data.client.get_account_state().await;
-    match account_state {
-        Ok(account_state) => {
-            let mut last_entry = data.cache.get_last_objects(1)?;
-            let balance_entry = account_state.to_bot_balance();
-            if !last_entry.is_empty() {
-                last_entry[0].timestamp = balance_entry.timestamp;
-            }
-            if last_entry.is_empty() || last_entry[0] != balance_entry {
-                data.cache.write(balance_entry).unwrap();
-            }
-        }
-        Err(e) => {
-            warn!("Error (Ignored) getting bots from cache: {}", e);
-        }
-    }
-
-    let graph = make_chart(&data.cache)?;
+async fn notify_bot_stats(
+    ctx: &poise::serenity_prelude::Context,
+    config: &Config<'_>,
+    target: &GuildTarget,
+    backends: &Backends,
+    balances: &[(&Backend, ConversionRate)],
+) -> Result<()> {
+    let announcement = &config.scheduled_chart_announcement;
+    let chart_options = &config.chart;
+    let settings = &target.settings;
+    let locale = config.locales().for_guild(target.guild_id);
+    let rate = &shown_rate(balances);
+    let private = config.privacy.applies_to(target.channel);
+    let labels = AccountLabels::new(config, backends, &locale, private)?;
+    let graph = make_chart(
+        backends,
+        balances,
+        chart_options,
+        &locale,
+        settings,
+        &labels,
+    )?;
     if graph.is_empty() {
         return Ok(());
     }
 
-    ctx.channel_id()
+    let shown: Vec<&Backend> = balances.iter().map(|(backend, _)| *backend).collect();
+    let summary = balance_summary(&shown, settings, &labels.naming)?;
+    let mut variables = summary_variables(summary.as_ref(), &locale, private);
+    variables.insert("date", locale.format_date(unix_timestamp()));
+    let mut message = CreateMessage::default().content(with_rate_note(
+        &template::render(&announcement.message, &variables)?,
+        rate,
+        &locale,
+    ));
+    if announcement.include_stats {
+        let stats = account_stats(
+            backends,
+            balances,
+            announcement.stats_period_days,
+            settings,
+            &labels,
+        )?;
+        message = message.embeds(
+            stats
+                .iter()
+                .take(10)
+                .map(|(account, stats)| stats_embed(account, stats, &rate.to, &locale, private))
+                .collect(),
+        );
+    }
+    target
+        .channel
         .send_files(
             ctx,
-            vec![CreateAttachment::bytes(graph, "graph.png")],
-            CreateMessage::default().content("Current profits / losses"),
+            vec![CreateAttachment::bytes(
+                graph,
+                chart_options.format.file_name(),
+            )],
+            message,
         )
         .await?;
 
-    reply
-        .edit(ctx, poise::CreateReply::default().content("Done!"))
-        .await?;
     Ok(())
 }
 
-async fn notify_bot_stats<'c>(
-    ctx: &poise::serenity_prelude::Context,
-    message: &str,
-    cache: &Arc<JsonCache<BotBalance>>,
-    channel: &ChannelId,
-) -> Result<()> {
-    let graph = make_chart(cache)?;
-    if graph.is_empty() {
-        return Ok(());
+/// Performance of an account, without its balance and flows in private channels
+fn stats_embed(
+    account: &str,
+    stats: &PerformanceStats,
+    currency: &str,
+    locale: &Locale,
+    private: bool,
+) -> CreateEmbed {
+    let not_available = locale.t("not_available");
+    let ratio = |value: Option<f64>| {
+        value
+            .and_then(Decimal::from_f64)
+            .map_or(not_available.clone(), |v| locale.format_decimal(v, 2))
+    };
+    let day = |d: &Option<DailyReturn>| {
+        d.as_ref().map_or(not_available.clone(), |d| {
+            format!("{} <t:{}:d>", locale.format_pct(d.ret), d.day)
+        })
+    };
+    let mut fields = vec![];
+    if !private {
+        fields.push((
+            locale.t("stats.balance"),
+            format!(
+                "{} → {} {}",
+                locale.format_amount(stats.start_value, currency),
+                locale.format_amount(stats.end_value, currency),
+                currency
+            ),
+            true,
+        ));
+        fields.push((
+            locale.t("stats.flows"),
+            format!(
+                "{}{} {}",
+                if stats.net_flows.is_sign_positive() {
+                    "+"
+                } else {
+                    ""
+                },
+                locale.format_amount(stats.net_flows, currency),
+                currency
+            ),
+            true,
+        ));
     }
+    fields.extend([
+        (
+            locale.t("stats.return"),
+            locale.format_pct(stats.period_return),
+            true,
+        ),
+        (
+            locale.t("stats.twr"),
+            locale.format_pct(stats.time_weighted_return),
+            true,
+        ),
+        (
+            locale.t("stats.max_drawdown"),
+            locale.format_pct(-stats.max_drawdown),
+            true,
+        ),
+        (
+            locale.t("stats.volatility"),
+            stats
+                .volatility
+                .map_or(not_available.clone(), |v| locale.format_pct(v)),
+            true,
+        ),
+        (locale.t("stats.sharpe"), ratio(stats.sharpe), true),
+        (locale.t("stats.sortino"), ratio(stats.sortino), true),
+        (locale.t("stats.best_day"), day(&stats.best_day), true),
+        (locale.t("stats.worst_day"), day(&stats.worst_day), true),
+    ]);
+    CreateEmbed::new()
+        .title(account)
+        .description(locale.tf(
+            "stats.period",
+            &[
+                ("start", format!("<t:{}:f>", stats.start_timestamp)),
+                ("end", format!("<t:{}:f>", stats.end_timestamp)),
+            ],
+        ))
+        .color(if stats.period_return >= 0.0 {
+            0x41d321
+        } else {
+            0xd32121
+        })
+        .fields(fields)
+}
 
-    channel
-        .send_files(
-            ctx,
-            vec![CreateAttachment::bytes(graph, "graph.png")],
-            CreateMessage::default().content(message),
-        )
-        .await?;
+/// Template variables of the balance summary, in private channels the balance is
+/// hidden and the PnL is a percentage
+fn summary_variables(
+    summary: Option<&BalanceSummary>,
+    locale: &Locale,
+    private: bool,
+) -> HashMap<&'static str, String> {
+    let mut variables = HashMap::new();
+    if let Some(summary) = summary.filter(|_| private) {
+        let start = summary.total - summary.pnl_24h;
+        variables.insert("total_balance", locale.t("privacy.hidden"));
+        variables.insert(
+            "pnl_24h",
+            share(summary.pnl_24h, start)
+                .and_then(|s| s.to_f64())
+                .map_or(locale.t("not_available"), |s| locale.format_pct(s)),
+        );
+        variables.insert("currency", String::new());
+    } else if let Some(summary) = summary {
+        variables.insert(
+            "total_balance",
+            locale.format_amount(summary.total, &summary.currency),
+        );
+        variables.insert(
+            "pnl_24h",
+            format!(
+                "{}{}",
+                if summary.pnl_24h.is_sign_positive() {
+                    "+"
+                } else {
+                    ""
+                },
+                locale.format_amount(summary.pnl_24h, &summary.currency)
+            ),
+        );
+        variables.insert("currency", summary.currency.clone());
+    }
+    variables
+}
 
-    Ok(())
+/// Shows the backend above the embed when there are several
+fn tag_embed(embed: CreateEmbed, label: Option<&str>) -> CreateEmbed {
+    match label {
+        Some(label) => embed.author(CreateEmbedAuthor::new(label)),
+        None => embed,
+    }
+}
+
+/// Channels for the trades and bot messages of `backend`, its own channel when it has
+/// one, otherwise the trades channel of each server
+fn trade_targets(
+    ctx: &poise::serenity_prelude::Context,
+    config: &Config<'_>,
+    guilds: &GuildSettingsStore,
+    backend: &str,
+) -> Vec<GuildTarget> {
+    match config.backend(backend).and_then(|b| b.trades_channel_id) {
+        Some(id) => {
+            let channel = ChannelId::new(id);
+            let guild_id = ctx.cache.channel(channel).map(|c| c.guild_id);
+            vec![GuildTarget {
+                guild_id,
+                channel,
+                settings: guilds.get(guild_id),
+            }]
+        }
+        None => guilds.targets(
+            ctx,
+            ChannelId::new(config.stats_channel_id),
+            true,
+            |s| s.trades_channel_id,
+            |s| s.trades_enabled,
+        ),
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn notify_trade<'c>(
     ctx: &poise::serenity_prelude::Context,
+    backend: &Backend,
+    label: Option<&str>,
     bot_name: &str,
     channel: &ChannelId,
     trade: &Trade<'c>,
+    template: &TradeEmbedTemplate,
+    summary: Option<&BalanceSummary>,
+    naming: &Naming,
+    locale: &Locale,
+    private: bool,
 ) -> Result<()> {
+    let mut variables = summary_variables(summary, locale, private);
+    variables.insert("bot", naming.bot(bot_name));
+    variables.insert("bot_full", bot_name.to_string());
+    variables.insert("backend", backend.name.clone());
+    variables.insert("exchange", naming.exchange(&trade.exchange));
+    variables.insert(
+        "pair",
+        format!("{}/{}", trade.base_asset, trade.quote_asset),
+    );
+    variables.insert("base", trade.base_asset.to_string());
+    variables.insert("quote", trade.quote_asset.to_string());
+    variables.insert(
+        "side",
+        locale.t(match trade.side {
+            TradeSide::Buy => "trade.side.buy",
+            TradeSide::Sell => "trade.side.sell",
+        }),
+    );
+    variables.insert(
+        "amount",
+        locale.format_amount(trade.amount, &trade.base_asset),
+    );
+    variables.insert(
+        "price",
+        locale.format_amount(trade.price, &trade.quote_asset),
+    );
+    variables.insert(
+        "notional",
+        locale.format_amount(trade.notional(), &trade.quote_asset),
+    );
+    if private {
+        // Custom layouts get the same masking: both amounts become the trade's share of
+        // the balance, which doesn't give away its size, and the price is hidden
+        let rate = backend.balances.cached_rate();
+        let shown = summary
+            .and_then(|s| notional_share(trade.notional(), &trade.quote_asset, &rate, s.total))
+            .map_or(locale.t("privacy.hidden"), |s| locale.format_share(s));
+        variables.insert("amount", shown.clone());
+        variables.insert("notional", shown);
+        variables.insert("price", locale.t("privacy.hidden"));
+    }
+
+    let fields = template
+        .fields
+        .iter()
+        .map(|field| {
+            Ok((
+                template::render(&field.name, &variables)?,
+                template::render(&field.value, &variables)?,
+                field.inline,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
     let embed = CreateEmbed::new()
-        .title("New trade")
+        .title(template::render(&template.title, &variables)?)
         .color(match trade.side {
-            TradeSide::Buy => 0x41d321,
-            TradeSide::Sell => 0xd32121,
+            TradeSide::Buy => parse_color(&template.buy_color)?,
+            TradeSide::Sell => parse_color(&template.sell_color)?,
         })
-        .description(format!(
-            "{} {}/{}",
-            trade.side, trade.base_asset, trade.quote_asset
-        ))
-        .fields(vec![
-            ("Bot", extract_bot_name(bot_name)?, false),
-            ("Amount", &trade.amount.to_string(), true),
+        .description(template::render(&template.description, &variables)?)
+        .fields(fields);
+    let builder = CreateMessage::new().add_embed(tag_embed(embed, label));
+    channel.send_message(ctx, builder).await?;
+    Ok(())
+}
+
+fn announcement_job(
+    schedule: &str,
+    ctx: poise::serenity_prelude::Context,
+    config: Arc<ConfigStore<'static>>,
+    backends: Arc<Backends>,
+    guilds: Arc<GuildSettingsStore>,
+) -> Result<Job> {
+    Ok(Job::new_async(schedule, move |uuid, mut l| {
+        let backends = backends.clone();
+        let guilds = guilds.clone();
+        let config = config.get();
+        let ctx = ctx.clone();
+        Box::pin(async move {
+            let announcement = &config.scheduled_chart_announcement;
+            // Backends that can't be reached are shown with their cached balances
+            let balances = update_balances(backends.all(), false).await;
+            let targets = guilds.targets(
+                &ctx,
+                ChannelId::new(announcement.channel_id),
+                announcement.enabled,
+                |s| s.announcement_channel_id,
+                |s| s.announcements_enabled,
+            );
+            for target in targets.iter() {
+                if let Err(e) = notify_bot_stats(&ctx, &config, target, &backends, &balances).await
+                {
+                    warn!("Error (Ignored) notifying bot stats: {}", e);
+                }
+            }
+
+            // Query the next execution time for this job
+            let next_tick = l.next_tick_for_job(uuid).await;
+            match next_tick {
+                Ok(Some(ts)) => debug!("Next time for job is {}", ts),
+                _ => debug!("Could not get next tick for job"),
+            }
+        })
+    })?)
+}
+
+/// Posts the trades that were held back by the rate limit as one embed
+async fn notify_digest(
+    ctx: &poise::serenity_prelude::Context,
+    channel: &ChannelId,
+    digest: &TradeDigest,
+    locale: &Locale,
+    private: bool,
+) -> Result<()> {
+    // Discord allows up to 25 fields per embed, the last one may be needed for the rest
+    let shown = if digest.entries.len() > 25 { 24 } else { 25 };
+    let mut fields: Vec<(String, String, bool)> = digest
+        .entries
+        .iter()
+        .take(shown)
+        .map(|((bot, pair), entry)| {
+            let net = entry.net_position();
+            let count = locale.tf("digest.trades", &[("count", entry.count.to_string())]);
+            if private {
+                return (format!("{} · {}", bot, pair), count, true);
+            }
             (
-                "Price",
-                format!("{} {}", trade.price, trade.quote_asset).as_ref(),
+                format!("{} · {}", bot, pair),
+                [
+                    count,
+                    locale.tf(
+                        "digest.buy",
+                        &[
+                            ("amount", locale.format_amount(entry.bought, &entry.base)),
+                            ("base", entry.base.clone()),
+                            (
+                                "notional",
+                                locale.format_amount(entry.bought_notional, &entry.quote),
+                            ),
+                            ("quote", entry.quote.clone()),
+                        ],
+                    ),
+                    locale.tf(
+                        "digest.sell",
+                        &[
+                            ("amount", locale.format_amount(entry.sold, &entry.base)),
+                            ("base", entry.base.clone()),
+                            (
+                                "notional",
+                                locale.format_amount(entry.sold_notional, &entry.quote),
+                            ),
+                            ("quote", entry.quote.clone()),
+                        ],
+                    ),
+                    locale.tf(
+                        "digest.net",
+                        &[
+                            (
+                                "amount",
+                                format!(
+                                    "{}{}",
+                                    if net.is_sign_positive() { "+" } else { "" },
+                                    locale.format_amount(net, &entry.base)
+                                ),
+                            ),
+                            ("base", entry.base.clone()),
+                        ],
+                    ),
+                ]
+                .join("\n"),
                 true,
+            )
+        })
+        .collect();
+    if digest.entries.len() > shown {
+        let rest: usize = digest.entries.values().skip(shown).map(|e| e.count).sum();
+        fields.push((
+            locale.t("digest.more_title"),
+            locale.tf(
+                "digest.more",
+                &[
+                    ("pairs", (digest.entries.len() - shown).to_string()),
+                    ("count", rest.to_string()),
+                ],
             ),
-        ]);
-    let builder = CreateMessage::new().add_embed(embed);
-    channel.send_message(ctx, builder).await?;
+            false,
+        ));
+    }
+    let embed = CreateEmbed::new()
+        .title(locale.t("digest.title"))
+        .description(locale.tf(
+            "digest.description",
+            &[
+                ("count", digest.count().to_string()),
+                ("since", format!("<t:{}:t>", digest.since)),
+            ],
+        ))
+        .fields(fields);
+    channel
+        .send_message(ctx, CreateMessage::new().add_embed(embed))
+        .await?;
     Ok(())
 }
 
-async fn pnl_cache_loop<'c>(
+/// Logs a failed backend request by how much attention it needs
+fn log_backend_error(action: &str, e: &anyhow::Error) {
+    match e.downcast_ref::<BackendError>() {
+        // Outages are reported by the health checks, not per skipped request
+        Some(BackendError::CircuitOpen { .. }) => debug!("Skipped {}: {}", action, e),
+        Some(BackendError::Auth { .. }) => {
+            error!("Error {}, check the backend credentials: {}", action, e)
+        }
+        _ => warn!("Error (Ignored) {}: {}", action, e),
+    }
+}
+
+/// Seconds between the balance updates for the balance alerts
+const BALANCE_ALERT_INTERVAL: u64 = 60;
+/// Characters of a backend error shown in Discord
+const ERROR_PREVIEW_LENGTH: usize = 300;
+
+async fn balance_alerts(
+    config: &Config<'_>,
+    alerts: &AlertEngine,
+    backends: &Backends,
+    backend: &Backend,
+    probes: &mut BalanceProbes,
+    now: u64,
+) -> Vec<AlertEvent> {
+    let rules: Vec<&AlertRule> = config
+        .alerts
+        .iter()
+        .filter(|r| r.metric == AlertMetric::BalanceDropPct && r.applies_to(&backend.name))
+        .collect();
+    let Some(window) = rules.iter().map(|r| r.window_minutes * 60).max() else {
+        return vec![];
+    };
+    let (balance, rate) = match backend.balances.probe().await {
+        Ok(probe) => probe,
+        Err(e) => {
+            log_backend_error("polling balances for alerts", &e);
+            return vec![];
+        }
+    };
+    probes.record(
+        balance,
+        &config.cash_flow_detection,
+        now.saturating_sub(window),
+    );
+    let series = account_series(probes.balances(), &rate);
+    let flows = account_flows(probes.flows(), &rate);
+    let naming = config.naming();
+    rules
+        .into_iter()
+        .flat_map(|rule| {
+            let measurements = balance_drop_measurements(rule, &series, &flows, now)
+                .into_iter()
+                .map(|m| Measurement {
+                    subject: backends.tag(backend, &naming.account(&m.subject)),
+                    value: m.value,
+                })
+                .collect();
+            alerts.evaluate(rule, measurements, now)
+        })
+        .collect()
+}
+
+async fn notify_alerts(
+    ctx: &poise::serenity_prelude::Context,
+    events: &[AlertEvent],
+    default_channel: ChannelId,
+    locales: &Locales,
+) {
+    for event in events {
+        let channel = event
+            .rule
+            .channel_id
+            .map_or(default_channel, ChannelId::new);
+        let locale = locales.for_guild(ctx.cache.channel(channel).map(|c| c.guild_id));
+        if let Err(e) = notify_alert(ctx, &channel, event, &locale).await {
+            warn!("Error (Ignored) notifying alert {}: {}", event.rule.name, e);
+        }
+    }
+}
+
+async fn notify_alert(
+    ctx: &poise::serenity_prelude::Context,
+    channel: &ChannelId,
+    event: &AlertEvent,
+    locale: &Locale,
+) -> Result<()> {
+    let rule = &event.rule;
+    let metric = locale.t(match rule.metric {
+        AlertMetric::GlobalPnlPct => "alert.metric.global_pnl_pct",
+        AlertMetric::InventoryImbalance => "alert.metric.inventory_imbalance",
+        AlertMetric::BalanceDropPct => "alert.metric.balance_drop_pct",
+    });
+    let severity = locale.t(match rule.severity {
+        AlertSeverity::Info => "alert.severity.info",
+        AlertSeverity::Warning => "alert.severity.warning",
+        AlertSeverity::Critical => "alert.severity.critical",
+    });
+    let mut variables = vec![
+        ("name", rule.name.clone()),
+        ("severity", severity),
+        ("metric", metric),
+        ("subject", event.subject.clone()),
+        ("value", locale.format_normalized(event.value.round_dp(4))),
+    ];
+    let embed = match event.kind {
+        AlertEventKind::Triggered => {
+            let threshold = [
+                rule.below.map(|below| {
+                    locale.tf(
+                        "alert.below",
+                        &[("threshold", locale.format_normalized(below))],
+                    )
+                }),
+                rule.above.map(|above| {
+                    locale.tf(
+                        "alert.above",
+                        &[("threshold", locale.format_normalized(above))],
+                    )
+                }),
+            ];
+            variables.push((
+                "threshold",
+                threshold
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ));
+            CreateEmbed::new()
+                .title(locale.tf("alert.triggered_title", &variables))
+                .description(locale.tf("alert.triggered", &variables))
+                .color(rule.severity.color())
+        }
+        AlertEventKind::Recovered => CreateEmbed::new()
+            .title(locale.tf("alert.recovered_title", &variables))
+            .description(locale.tf("alert.recovered", &variables))
+            .color(0x41d321),
+    };
+    let mentions: Vec<String> = rule
+        .mention_roles
+        .iter()
+        .map(|role| format!("<@&{}>", role))
+        .collect();
+    let message = CreateMessage::new()
+        .content(mentions.join(" "))
+        .allowed_mentions(
+            CreateAllowedMentions::new().roles(rule.mention_roles.iter().copied().map(RoleId::new)),
+        )
+        .add_embed(embed);
+    channel.send_message(ctx, message).await?;
+    Ok(())
+}
+
+async fn notify_health(
+    ctx: &poise::serenity_prelude::Context,
+    channel: ChannelId,
+    label: Option<&str>,
+    events: &[HealthEvent],
+    locales: &Locales,
+) {
+    let locale = locales.for_guild(ctx.cache.channel(channel).map(|c| c.guild_id));
+    for event in events {
+        let embed = match event {
+            HealthEvent::Outage {
+                endpoint,
+                since,
+                failures,
+                error,
+            } => {
+                let variables = [
+                    ("endpoint", endpoint.clone()),
+                    ("since", format!("<t:{}:f>", since)),
+                    ("failures", failures.to_string()),
+                    ("error", truncate(error, ERROR_PREVIEW_LENGTH)),
+                ];
+                CreateEmbed::new()
+                    .title(locale.tf("health.outage_title", &variables))
+                    .description(locale.tf("health.outage", &variables))
+                    .color(0xd32f2f)
+            }
+            HealthEvent::Recovered { endpoint, downtime } => {
+                let variables = [
+                    ("endpoint", endpoint.clone()),
+                    ("downtime", format_duration(Duration::from_secs(*downtime))),
+                ];
+                CreateEmbed::new()
+                    .title(locale.tf("health.recovered_title", &variables))
+                    .description(locale.tf("health.recovered", &variables))
+                    .color(0x41d321)
+            }
+        };
+        if let Err(e) = channel
+            .send_message(ctx, CreateMessage::new().add_embed(tag_embed(embed, label)))
+            .await
+        {
+            warn!("Error (Ignored) notifying backend health: {}", e);
+        }
+    }
+}
+
+async fn notify_lifecycle(
+    ctx: &poise::serenity_prelude::Context,
+    config: &Config<'_>,
+    guilds: &GuildSettingsStore,
+    backend: &str,
+    label: Option<&str>,
+    events: &[LifecycleEvent],
+    locales: &Locales,
+) {
+    if !config.bot_lifecycle.enabled || events.is_empty() {
+        return;
+    }
+    let targets = match config.bot_lifecycle.channel_id {
+        Some(id) => {
+            let channel = ChannelId::new(id);
+            vec![GuildTarget {
+                guild_id: ctx.cache.channel(channel).map(|c| c.guild_id),
+                channel,
+                settings: GuildSettings::default(),
+            }]
+        }
+        None => trade_targets(ctx, config, guilds, backend),
+    };
+    let naming = config.naming();
+    for event in events {
+        let bot_name = match event {
+            LifecycleEvent::Appeared { bot, .. }
+            | LifecycleEvent::Disappeared { bot, .. }
+            | LifecycleEvent::StatusChanged { bot, .. }
+            | LifecycleEvent::ControllerStatusChanged { bot, .. } => bot,
+        };
+        for target in targets.iter().filter(|t| t.settings.shows_bot(bot_name)) {
+            let locale = locales.for_guild(target.guild_id);
+            let private = config.privacy.applies_to(target.channel);
+            let embed = lifecycle_embed(event, &naming.bot(bot_name), &locale, private);
+            let embed = tag_embed(embed, label);
+            let message = CreateMessage::new().add_embed(embed);
+            if let Err(e) = target.channel.send_message(ctx, message).await {
+                warn!(
+                    "Error (Ignored) notifying status of bot {}: {}",
+                    bot_name, e
+                );
+            }
+        }
+    }
+}
+
+/// Message for a lifecycle event, without the PnL in quote in private channels
+fn lifecycle_embed(
+    event: &LifecycleEvent,
+    bot: &str,
+    locale: &Locale,
+    private: bool,
+) -> CreateEmbed {
+    let mut variables = vec![("bot", bot.to_string())];
+    let pnl_fields = |snapshot: &BotSnapshot| {
+        if private {
+            return vec![];
+        }
+        [
+            ("lifecycle.field.realized_pnl", snapshot.realized_pnl_quote),
+            (
+                "lifecycle.field.unrealized_pnl",
+                snapshot.unrealized_pnl_quote,
+            ),
+            ("lifecycle.field.global_pnl", snapshot.global_pnl_quote),
+            ("lifecycle.field.volume", snapshot.volume_traded),
+        ]
+        .into_iter()
+        .map(|(key, value)| (locale.t(key), locale.format_compact(value), true))
+        .collect::<Vec<_>>()
+    };
+    match event {
+        LifecycleEvent::Appeared { snapshot, .. } => {
+            variables.push(("status", snapshot.status.clone()));
+            CreateEmbed::new()
+                .title(locale.tf("lifecycle.appeared_title", &variables))
+                .description(locale.tf("lifecycle.appeared", &variables))
+                .color(0x41d321)
+        }
+        LifecycleEvent::Disappeared { last, .. } => CreateEmbed::new()
+            .title(locale.tf("lifecycle.disappeared_title", &variables))
+            .description(locale.tf("lifecycle.disappeared", &variables))
+            .fields(pnl_fields(last))
+            .color(0x808080),
+        LifecycleEvent::StatusChanged {
+            from, to, snapshot, ..
+        } => {
+            variables.push(("from", from.clone()));
+            variables.push(("to", to.clone()));
+            let embed = CreateEmbed::new()
+                .title(locale.tf("lifecycle.status_title", &variables))
+                .description(locale.tf("lifecycle.status", &variables));
+            if snapshot.is_stopped() {
+                // The last totals of a stopped bot are its final result
+                embed.fields(pnl_fields(snapshot)).color(0x808080)
+            } else {
+                embed.color(0xf5a623)
+            }
+        }
+        LifecycleEvent::ControllerStatusChanged {
+            controller,
+            from,
+            to,
+            ..
+        } => {
+            let none = locale.t("lifecycle.none");
+            variables.push(("controller", controller.clone()));
+            variables.push(("from", from.clone().unwrap_or_else(|| none.clone())));
+            variables.push(("to", to.clone().unwrap_or(none)));
+            CreateEmbed::new()
+                .title(locale.tf("lifecycle.controller_title", &variables))
+                .description(locale.tf("lifecycle.controller", &variables))
+                .color(0xf5a623)
+        }
+    }
+}
+
+/// Runs the announcement job, `scheduler` keeps the scheduler of the previous run so a
+/// restart can stop it before starting a new one
+async fn pnl_cache_loop(
     ctx: poise::serenity_prelude::Context,
-    config: &Config<'c>,
-    client: Arc<BackendAPIClient>,
-    cache: Arc<JsonCache<BotBalance>>,
+    config: Arc<ConfigStore<'static>>,
+    backends: Arc<Backends>,
+    guilds: Arc<GuildSettingsStore>,
+    scheduler: Arc<tokio::sync::Mutex<Option<JobScheduler>>>,
+    mut shutdown: Shutdown,
 ) -> Result<()> {
-    let sched = JobScheduler::new().await?;
-    let message = config
+    let mut sched = {
+        let mut scheduler = scheduler.lock().await;
+        if let Some(mut previous) = scheduler.take() {
+            previous.shutdown().await?;
+        }
+        scheduler.insert(JobScheduler::new().await?).clone()
+    };
+    let mut schedule = config
+        .get()
         .scheduled_chart_announcement
-        .message
-        .to_string()
-        .clone();
-    let chart_announcement_channel = ChannelId::new(config.scheduled_chart_announcement.channel_id);
-    let schedule = config.scheduled_chart_announcement.schedule.to_string();
-    sched
-        .add(Job::new_async(schedule.as_str(), move |uuid, mut l| {
-            let client = client.clone();
-            let cache = cache.clone();
-            let message = message.clone();
-            let ctx = ctx.clone();
-            Box::pin(async move {
-                let account_state = client.get_account_state().await;
-                match account_state {
-                    Ok(account_state) => {
-                        let balance_entry = account_state.to_bot_balance();
-                        cache.write(balance_entry).unwrap();
-                        if let Err(e) =
-                            notify_bot_stats(&ctx, &message, &cache, &chart_announcement_channel)
-                                .await
-                        {
-                            warn!("Error (Ignored) notifying bot stats: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        warn!("Error (Ignored) getting bots from cache: {}", e);
-                    }
-                }
+        .schedule
+        .to_string();
+    let job = announcement_job(
+        &schedule,
+        ctx.clone(),
+        config.clone(),
+        backends.clone(),
+        guilds.clone(),
+    )?;
+    let mut job_id = sched.add(job).await?;
+    sched.start().await?;
 
-                // Query the next execution time for this job
-                let next_tick = l.next_tick_for_job(uuid).await;
-                match next_tick {
-                    Ok(Some(ts)) => debug!("Next time for job is {}", ts),
-                    _ => debug!("Could not get next tick for job"),
+    // Swap the job when /config changes the schedule
+    let mut changes = config.subscribe();
+    loop {
+        tokio::select! {
+            changed = changes.changed() => {
+                if changed.is_err() {
+                    break;
                 }
-            })
-        })?)
-        .await?;
-    sched.start().await?;
+            }
+            _ = shutdown.changed() => break,
+        }
+        let new_schedule = config
+            .get()
+            .scheduled_chart_announcement
+            .schedule
+            .to_string();
+        if new_schedule == schedule {
+            continue;
+        }
+        let job = announcement_job(
+            &new_schedule,
+            ctx.clone(),
+            config.clone(),
+            backends.clone(),
+            guilds.clone(),
+        );
+        let replaced = match job {
+            Ok(job) => match sched.remove(&job_id).await {
+                Ok(()) => sched.add(job).await,
+                Err(e) => Err(e),
+            },
+            Err(e) => {
+                warn!("Error (Ignored) creating announcement job: {}", e);
+                continue;
+            }
+        };
+        match replaced {
+            Ok(id) => {
+                job_id = id;
+                schedule = new_schedule;
+            }
+            Err(e) => warn!("Error (Ignored) rescheduling announcements: {}", e),
+        }
+    }
+    sched.shutdown().await?;
+    *scheduler.lock().await = None;
     Ok(())
 }
 
-async fn trade_loop<'c>(
+/// What the poller remembers of one backend between polls
+#[derive(Default)]
+struct BackendPoll {
+    timestamps: HashMap<String, u64>,
+    lifecycle: BotLifecycleTracker,
+    probes: BalanceProbes,
+}
+
+/// What the poller remembers between polls, kept when it's restarted so trades that were
+/// already posted aren't posted again
+#[derive(Default)]
+struct PollerState {
+    polls: HashMap<String, BackendPoll>,
+    throttle: TradeThrottle,
+    alerts: AlertEngine,
+    last_balance_check: u64,
+}
+
+async fn trade_loop(
     ctx: poise::serenity_prelude::Context,
-    config: &Config<'c>,
-    client: Arc<BackendAPIClient>,
+    config: Arc<ConfigStore<'static>>,
+    backends: Arc<Backends>,
+    guilds: Arc<GuildSettingsStore>,
+    mutes: Arc<MuteStore>,
+    state: Arc<tokio::sync::Mutex<PollerState>>,
+    mut shutdown: Shutdown,
 ) -> Result<()> {
-    let stats_channel = ChannelId::new(config.stats_channel_id);
-    tokio::spawn(async move {
-        let mut timestamps: HashMap<String, u64> = HashMap::new();
-        loop {
-            sleep_until(Instant::now() + Duration::from_secs(10)).await;
-            let bots = match client.get_bots().await {
+    loop {
+        tokio::select! {
+            _ = sleep_until(Instant::now() + Duration::from_secs(10)) => {}
+            _ = shutdown.changed() => return Ok(()),
+        }
+        let mut state = state.lock().await;
+        let PollerState {
+            polls,
+            throttle,
+            alerts,
+            last_balance_check,
+        } = &mut *state;
+        let config = config.get();
+        let stats_channel = ChannelId::new(config.stats_channel_id);
+        let locales = config.locales();
+        let naming = config.naming();
+        // Balance summaries by the accounts a server shows, read once per poll
+        let mut summaries: HashMap<Option<Vec<String>>, Option<BalanceSummary>> = HashMap::new();
+        for (channel, digest) in throttle.due_digests(&config.rate_limit, unix_timestamp()) {
+            let locale = locales.for_guild(digest.guild_id);
+            let private = config.privacy.applies_to(channel);
+            if let Err(e) = notify_digest(&ctx, &channel, &digest, &locale, private).await {
+                warn!("Error (Ignored) notifying trade digest: {}", e);
+            }
+        }
+        let now = unix_timestamp();
+        let checks_balance = config
+            .alerts
+            .iter()
+            .any(|r| r.metric == AlertMetric::BalanceDropPct)
+            && now >= *last_balance_check + BALANCE_ALERT_INTERVAL;
+        if checks_balance {
+            *last_balance_check = now;
+        }
+        for backend in backends.all() {
+            let label = backends.label(backend);
+            let client = &backend.client;
+            let events = client
+                .health()
+                .check(config.health_checks.failure_threshold);
+            let ops_channel = config.backend_ops_channel(&backend.name);
+            notify_health(&ctx, ops_channel, label, &events, &locales).await;
+            let poll = polls.entry(backend.name.clone()).or_default();
+            if checks_balance {
+                let events =
+                    balance_alerts(&config, alerts, &backends, backend, &mut poll.probes, now)
+                        .await;
+                notify_alerts(&ctx, &events, stats_channel, &locales).await;
+            }
+            let response = match client.get_bots().await {
                 Ok(response) => response,
+                Err(BackendError::CircuitOpen { until }) => {
+                    debug!(
+                        "Not polling bots of {} while the backend is paused until {}",
+                        backend.name, until
+                    );
+                    continue;
+                }
+                Err(e @ BackendError::Auth { .. }) => {
+                    error!("Error polling bots, check the backend credentials: {}", e);
+                    continue;
+                }
                 Err(e) => {
                     warn!("get_bots error (ignored): {}", e);
                     continue;
                 }
-            }
-            .to_internal_bots();
-            timestamps.retain(|k, _| bots.iter().any(|b| b.name == k.as_str()));
+            };
+            let events: Vec<AlertEvent> = config
+                .alerts
+                .iter()
+                .filter(|r| r.metric != AlertMetric::BalanceDropPct && r.applies_to(&backend.name))
+                .flat_map(|rule| {
+                    let measurements = bot_measurements(rule, &response, &naming)
+                        .into_iter()
+                        .map(|m| Measurement {
+                            subject: backends.tag(backend, &m.subject),
+                            value: m.value,
+                        })
+                        .collect();
+                    alerts.evaluate(rule, measurements, now)
+                })
+                .collect();
+            notify_alerts(&ctx, &events, stats_channel, &locales).await;
+            let events = poll.lifecycle.update(&response);
+            notify_lifecycle(
+                &ctx,
+                &config,
+                &guilds,
+                &backend.name,
+                label,
+                &events,
+                &locales,
+            )
+            .await;
+            let bots = response.to_internal_bots();
+            poll.timestamps
+                .retain(|k, _| bots.iter().any(|b| b.name == k.as_str()));
             for bot in bots.into_iter() {
-                let latest_trade = bot.get_latest_trade(&client).await;
-                match latest_trade {
-                    Ok(trade) => {
-                        let trade = match trade {
-                            Some(trade) => trade,
-                            None => {
-                                continue;
+                let trades = match bot.get_trades(client).await {
+                    Ok(trades) => trades,
+                    Err(e) => {
+                        match e.downcast_ref::<BackendError>() {
+                            // The other bots would fail the same way
+                            Some(BackendError::CircuitOpen { .. } | BackendError::Auth { .. }) => {
+                                log_backend_error("getting trades", &e);
+                                break;
                             }
-                        };
-                        let last_timestamp = timestamps.entry(bot.name.to_string()).or_insert(0);
-                        if *last_timestamp != trade.timestamp {
-                            notify_trade(&ctx, &bot.name, &stats_channel, &trade)
-                                .await
-                                .unwrap();
-                            *last_timestamp = trade.timestamp;
+                            // E.g. a bot that has no history yet
+                            Some(BackendError::Backend { .. }) => {
+                                warn!("Error (Ignored) getting trades for bot {}: {}", bot.name, e);
+                            }
+                            _ => error!("Error getting trades for bot {}: {}", bot.name, e),
                         }
+                        continue;
                     }
-                    Err(e) => {
-                        error!("Error getting latest trade for bot {}: {}", bot.name, e);
+                };
+                let seen = poll.timestamps.get(bot.name.as_ref()).copied();
+                let latest = trades.iter().map(|t| t.timestamp).max().unwrap_or(0);
+                poll.timestamps
+                    .insert(bot.name.to_string(), latest.max(seen.unwrap_or(0)));
+                let trades = new_trades(trades, seen);
+                if trades.is_empty()
+                    || mutes.is_muted(&bot.name)
+                    || config
+                        .muted_bots
+                        .iter()
+                        .any(|m| matches_bot_name(m, &bot.name))
+                {
+                    continue;
+                }
+                for trade in trades.iter() {
+                    let mut targets = trade_targets(&ctx, &config, &guilds, &backend.name);
+                    for route in config.trade_routes.iter() {
+                        let channel = ChannelId::new(route.channel_id);
+                        if route.matches(&bot.name, trade)
+                            && !targets.iter().any(|t| t.channel == channel)
+                        {
+                            targets.push(GuildTarget {
+                                guild_id: ctx.cache.channel(channel).map(|c| c.guild_id),
+                                channel,
+                                settings: GuildSettings::default(),
+                            });
+                        }
+                    }
+                    for target in targets.iter() {
+                        let settings = &target.settings;
+                        if !settings.shows_bot(&bot.name) {
+                            continue;
+                        }
+                        let admitted = throttle.admit(
+                            &config.rate_limit,
+                            target.channel,
+                            target.guild_id,
+                            label,
+                            &naming.bot(&bot.name),
+                            trade,
+                            unix_timestamp(),
+                        );
+                        if !admitted {
+                            continue;
+                        }
+                        let locale = locales.for_guild(target.guild_id);
+                        let private = config.privacy.applies_to(target.channel);
+                        let template = config.trade_embed.clone().unwrap_or_else(|| {
+                            if private {
+                                TradeEmbedTemplate::localized_private(&locale)
+                            } else {
+                                TradeEmbedTemplate::localized(&locale)
+                            }
+                        });
+                        // Private trades are shown as a share of the total balance
+                        let summary = if private || template.uses_summary() {
+                            summaries
+                                .entry(settings.visible_accounts.clone())
+                                .or_insert_with(|| {
+                                    balance_summary(&backends.all(), settings, &naming)
+                                        .unwrap_or_else(|e| {
+                                            warn!("Error (Ignored) summarizing balances: {}", e);
+                                            None
+                                        })
+                                })
+                                .as_ref()
+                        } else {
+                            None
+                        };
+                        notify_trade(
+                            &ctx,
+                            backend,
+                            label,
+                            &bot.name,
+                            &target.channel,
+                            trade,
+                            &template,
+                            summary,
+                            &naming,
+                            &locale,
+                            private,
+                        )
+                        .await
+                        .unwrap_or_else(|e| {
+                            // One server's missing channel shouldn't stop the others
+                            warn!("Error (Ignored) notifying trade: {}", e);
+                        });
                     }
                 }
             }
         }
-    });
+    }
+}
+
+/// Stops the bot right away when the backend rejects its credentials, an unreachable
+/// backend is left to the health checks
+async fn check_backend_credentials(backends: &Backends) -> Result<()> {
+    for backend in backends.all() {
+        match backend.client.check_credentials().await {
+            Err(BackendError::Auth { url, status }) => {
+                return Err(anyhow!(
+                    "Backend \"{}\" rejected the configured credentials with status {} ({}), check its auth in the config file",
+                    backend.name,
+                    status,
+                    url
+                ))
+            }
+            Err(e) => warn!(
+                "Couldn't check the credentials of backend \"{}\": {}",
+                backend.name, e
+            ),
+            Ok(()) => {}
+        }
+    }
     Ok(())
 }
 
@@ -313,6 +1495,7 @@ fn init_config<'c>(path: &PathBuf) -> Result<Config<'c>> {
         let bytes = std::fs::read(path)?;
         let contents = String::from_utf8_lossy(&bytes);
         let config: Config = serde_yaml::from_str(&contents)?;
+        config.validate()?;
         fs::create_dir_all(&config.cache_path)?;
         Ok(config)
     } else {
@@ -327,39 +1510,162 @@ fn init_config<'c>(path: &PathBuf) -> Result<Config<'c>> {
     }
 }
 
+/// The `/config` changes, within the cache path
+const CONFIG_OVERRIDES_FILE: &str = "config_overrides.yml";
+/// First wait before restarting a task that stopped, doubled per restart in a row
+const TASK_RESTART_DELAY: Duration = Duration::from_secs(5);
+const MAX_TASK_RESTART_DELAY: Duration = Duration::from_secs(300);
+/// Time the tasks get to finish their work on shutdown, Docker kills after 10 seconds
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(8);
+
+/// Waits for Ctrl+C or the SIGTERM of `docker stop`
+async fn shutdown_signal() -> Result<()> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result?,
+        _ = terminate.recv() => {}
+    }
+    Ok(())
+}
+
+/// Posts the restarts of the background tasks to the ops channel
+async fn report_restarts(
+    ctx: poise::serenity_prelude::Context,
+    config: Arc<ConfigStore<'static>>,
+    mut restarts: UnboundedReceiver<TaskRestart>,
+) {
+    while let Some(restart) = restarts.recv().await {
+        let config = config.get();
+        let channel = config.ops_channel();
+        let locale = config
+            .locales()
+            .for_guild(ctx.cache.channel(channel).map(|c| c.guild_id));
+        let variables = [
+            ("task", restart.task.to_string()),
+            ("reason", truncate(&restart.reason, ERROR_PREVIEW_LENGTH)),
+            ("restarts", restart.restarts.to_string()),
+            ("delay", format_duration(restart.delay)),
+        ];
+        let embed = CreateEmbed::new()
+            .title(locale.tf("supervisor.restart_title", &variables))
+            .description(locale.tf("supervisor.restart", &variables))
+            .color(0xd32f2f);
+        if let Err(e) = channel
+            .send_message(&ctx, CreateMessage::new().add_embed(embed))
+            .await
+        {
+            warn!("Error (Ignored) reporting task restart: {}", e);
+        }
+    }
+}
+
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     env_logger::init();
-    let args = Args::parse();
-    let config = init_config(&args.config_path).unwrap();
+    match run(Args::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<()> {
+    let config = init_config(&args.config_path)?;
+    let overrides_path = config.cache_path.join(CONFIG_OVERRIDES_FILE);
+    let store = ConfigStore::new(overrides_path, config)?;
+    let config = store.get();
+    set_bot_name_patterns(&config.bot_name_patterns)?;
     let intents = serenity::GatewayIntents::non_privileged();
     let bot_token = config.bot_token.clone();
-    let client = Arc::new(BackendAPIClient::new(config.backend_api_base_url.clone()));
-    let cache = Arc::new(JsonCache::new(config.cache_path.join("balance.jsonl")));
+    let backends = Arc::new(Backends::from_config(&config)?);
+    check_backend_credentials(&backends).await?;
+    let guilds = Arc::new(GuildSettingsStore::load(&config.cache_path)?);
+    let mutes = Arc::new(MuteStore::load(&config.cache_path)?);
+    let config = Arc::new(store);
+    let (supervisor, restarts) = Supervisor::new(TASK_RESTART_DELAY, MAX_TASK_RESTART_DELAY);
+    let supervisor = Arc::new(supervisor);
+    let tasks = supervisor.clone();
 
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![profit_chart(), stats_announcement_test()],
+            commands: vec![
+                commands::profit_chart::profit_chart(),
+                commands::stats::stats(),
+                commands::cash_flow::cash_flow(),
+                stats_announcement_test(),
+                commands::setup::setup(),
+                commands::config::config(),
+                commands::mute::mute(),
+                commands::mute::unmute(),
+                commands::mute::pause_notifications(),
+                commands::mute::resume_notifications(),
+                commands::health::health(),
+                commands::bot::bot(),
+                commands::bots::bots(),
+            ],
             ..Default::default()
         })
         .setup(move |ctx, _ready, framework| {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                trade_loop(ctx.clone(), &config, client.clone()).await?;
-                if config.scheduled_chart_announcement.enabled {
-                    pnl_cache_loop(ctx.clone(), &config, client.clone(), cache.clone()).await?;
-                }
+                tokio::spawn(report_restarts(ctx.clone(), config.clone(), restarts));
+                let state = Arc::new(tokio::sync::Mutex::new(PollerState::default()));
+                tasks.spawn("trade poller", {
+                    let (ctx, config, backends) = (ctx.clone(), config.clone(), backends.clone());
+                    let (guilds, mutes) = (guilds.clone(), mutes.clone());
+                    move |shutdown| {
+                        trade_loop(
+                            ctx.clone(),
+                            config.clone(),
+                            backends.clone(),
+                            guilds.clone(),
+                            mutes.clone(),
+                            state.clone(),
+                            shutdown,
+                        )
+                    }
+                });
+                // Servers can turn announcements on with /setup, so the job always runs
+                tasks.spawn("announcement scheduler", {
+                    let (ctx, config, backends) = (ctx.clone(), config.clone(), backends.clone());
+                    let guilds = guilds.clone();
+                    let scheduler = Arc::new(tokio::sync::Mutex::new(None));
+                    move |shutdown| {
+                        pnl_cache_loop(
+                            ctx.clone(),
+                            config.clone(),
+                            backends.clone(),
+                            guilds.clone(),
+                            scheduler.clone(),
+                            shutdown,
+                        )
+                    }
+                });
                 Ok(Data {
                     config,
-                    client,
-                    cache,
+                    backends,
+                    guilds,
+                    mutes,
                 })
             })
         })
         .build();
 
-    let client = serenity::ClientBuilder::new(&bot_token, intents)
+    let mut client = serenity::ClientBuilder::new(&bot_token, intents)
         .framework(framework)
-        .await;
-    client.unwrap().start().await.unwrap();
+        .await?;
+    let shard_manager = client.shard_manager.clone();
+    tokio::spawn(async move {
+        if let Err(e) = shutdown_signal().await {
+            error!("Error listening for shutdown signals: {}", e);
+            return;
+        }
+        info!("Shutting down");
+        supervisor.shutdown(SHUTDOWN_GRACE).await;
+        shard_manager.shutdown_all().await;
+    });
+    client.start().await?;
+    Ok(())
 }
@@ -0,0 +1,187 @@
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::backend_api::objects::ActiveBotsResponse;
+use crate::config::{AlertMetric, AlertRule, CashFlowDetection};
+use crate::utils::extract_bot_name::matches_bot_name;
+
+use super::bot_balance::BotBalance;
+use super::cash_flow::{detect_cash_flows, CashFlow};
+use super::naming::Naming;
+
+/// Value of a rule's metric for one bot controller or account
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub subject: String,
+    pub value: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertEventKind {
+    Triggered,
+    Recovered,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertEvent {
+    pub rule: AlertRule,
+    pub subject: String,
+    pub value: Decimal,
+    pub kind: AlertEventKind,
+}
+
+#[derive(Default)]
+struct AlertState {
+    active: bool,
+    /// Whether the current episode was reported, so its recovery is reported too
+    reported: bool,
+    last_reported: Option<u64>,
+}
+
+/// Remembers which alerts are active, to apply cooldowns and report recoveries
+#[derive(Default)]
+pub struct AlertEngine {
+    states: Mutex<HashMap<(String, String), AlertState>>,
+}
+
+impl AlertEngine {
+    pub fn evaluate(
+        &self,
+        rule: &AlertRule,
+        measurements: Vec<Measurement>,
+        now: u64,
+    ) -> Vec<AlertEvent> {
+        let mut states = self.states.lock().unwrap();
+        let cooldown = rule.cooldown_minutes * 60;
+        let mut events = vec![];
+        for Measurement { subject, value } in measurements {
+            let state = states
+                .entry((rule.name.clone(), subject.clone()))
+                .or_default();
+            let kind = if rule.is_triggered(value) {
+                state.active = true;
+                if state
+                    .last_reported
+                    .is_some_and(|last| last + cooldown > now)
+                {
+                    continue;
+                }
+                state.reported = true;
+                state.last_reported = Some(now);
+                AlertEventKind::Triggered
+            } else if state.active {
+                state.active = false;
+                if !std::mem::take(&mut state.reported) {
+                    continue;
+                }
+                AlertEventKind::Recovered
+            } else {
+                continue;
+            };
+            events.push(AlertEvent {
+                rule: rule.clone(),
+                subject,
+                value,
+                kind,
+            });
+        }
+        events
+    }
+}
+
+/// Controller metrics of the running bots that `rule` applies to
+pub fn bot_measurements(
+    rule: &AlertRule,
+    bots: &ActiveBotsResponse,
+    naming: &Naming,
+) -> Vec<Measurement> {
+    bots.data
+        .iter()
+        .filter(|(name, _)| {
+            rule.bot
+                .as_ref()
+                .is_none_or(|bot| matches_bot_name(bot, name))
+        })
+        .flat_map(|(name, bot)| {
+            let shown = naming.bot(name);
+            bot.performance.iter().filter_map(move |(controller, c)| {
+                let value = match rule.metric {
+                    AlertMetric::GlobalPnlPct => c.performance.global_pnl_pct,
+                    AlertMetric::InventoryImbalance => c.performance.inventory_imbalance,
+                    AlertMetric::BalanceDropPct => return None,
+                };
+                Some(Measurement {
+                    subject: format!("{} / {}", shown, controller),
+                    value,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Balances polled for the balance alerts, kept in memory so they don't end up in the
+/// chart history
+#[derive(Default)]
+pub struct BalanceProbes {
+    balances: Vec<BotBalance>,
+    flows: Vec<CashFlow>,
+}
+
+impl BalanceProbes {
+    /// Adds a balance, detecting deposits and withdrawals since the previous one, and
+    /// forgets everything before `keep_since`
+    pub fn record(&mut self, balance: BotBalance, detection: &CashFlowDetection, keep_since: u64) {
+        if let Some(previous) = self.balances.last().filter(|_| detection.enabled) {
+            self.flows
+                .extend(detect_cash_flows(previous, &balance, detection));
+        }
+        self.balances.push(balance);
+        self.balances.retain(|b| b.timestamp >= keep_since);
+        self.flows.retain(|f| f.timestamp >= keep_since);
+    }
+
+    pub fn balances(&self) -> &[BotBalance] {
+        &self.balances
+    }
+
+    pub fn flows(&self) -> &[CashFlow] {
+        &self.flows
+    }
+}
+
+/// Largest fall in percent within the rule's window for each account, with the
+/// deposits and withdrawals made since the peak left out
+pub fn balance_drop_measurements(
+    rule: &AlertRule,
+    series: &BTreeMap<String, Vec<(u64, Decimal)>>,
+    flows: &HashMap<String, Vec<(u64, Decimal)>>,
+    now: u64,
+) -> Vec<Measurement> {
+    let window_start = now.saturating_sub(rule.window_minutes * 60);
+    series
+        .iter()
+        .filter(|(account, _)| rule.account.as_ref().is_none_or(|a| a == *account))
+        .filter_map(|(account, points)| {
+            let &(last_ts, last_value) = points.last()?;
+            let flows = flows.get(account).map_or(&[][..], |f| f.as_slice());
+            let drop = points
+                .iter()
+                .filter(|(ts, value)| *ts >= window_start && value.is_sign_positive())
+                .filter(|(_, value)| !value.is_zero())
+                .map(|(ts, peak)| {
+                    let flows: Decimal = flows
+                        .iter()
+                        .filter(|(flow_ts, _)| flow_ts > ts && *flow_ts <= last_ts)
+                        .map(|(_, amount)| amount)
+                        .sum();
+                    (peak - (last_value - flows)) / peak * Decimal::ONE_HUNDRED
+                })
+                .max()?;
+            Some(Measurement {
+                subject: account.clone(),
+                value: drop.max(Decimal::ZERO).round_dp(2),
+            })
+        })
+        .collect()
+}
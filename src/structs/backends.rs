@@ -0,0 +1,102 @@
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+
+use crate::backend_api::client::BackendAPIClient;
+use crate::backend_api::credentials::backend_headers;
+use crate::config::{BackendAuth, Config};
+
+use super::balance_tracker::BalanceTracker;
+use super::currency_converter::CurrencyConverter;
+
+/// One backend API deploy, with the balances tracked from it
+pub struct Backend {
+    pub name: String,
+    pub client: Arc<BackendAPIClient>,
+    pub balances: Arc<BalanceTracker>,
+}
+
+/// All backends the bot polls, in the order of the config file
+pub struct Backends {
+    backends: Vec<Backend>,
+}
+
+impl Backends {
+    pub fn from_config(config: &Config) -> Result<Backends> {
+        let named = !config.backends.is_empty();
+        let mut backends = vec![];
+        for backend in config.backend_configs() {
+            // Only the single backend from `backendApiBaseUrl` falls back to the
+            // environment, so its login isn't sent to other deploys
+            let auth = match (named, backend.auth) {
+                (true, auth) => Some(auth.unwrap_or(BackendAuth::None)),
+                (false, auth) => auth,
+            };
+            let headers = backend_headers(auth.as_ref(), &backend.headers)
+                .map_err(|e| anyhow!("Backend \"{}\": {}", backend.name, e))?;
+            let client = Arc::new(BackendAPIClient::new(
+                backend.base_url,
+                config.backend_requests.clone(),
+                headers,
+            )?);
+            let cache_path = if named {
+                config.cache_path.join(&backend.name)
+            } else {
+                config.cache_path.clone()
+            };
+            std::fs::create_dir_all(&cache_path)?;
+            let balances = Arc::new(BalanceTracker::new(
+                client.clone(),
+                CurrencyConverter::new(
+                    config.reporting_currency.clone(),
+                    &config.backend_requests,
+                )?,
+                config.cash_flow_detection.clone(),
+                &cache_path,
+            ));
+            backends.push(Backend {
+                name: backend.name,
+                client,
+                balances,
+            });
+        }
+        Ok(Backends { backends })
+    }
+
+    pub fn all(&self) -> Vec<&Backend> {
+        self.backends.iter().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Backend> {
+        self.backends
+            .iter()
+            .find(|b| b.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The backend a command was limited to, or all of them
+    pub fn select(&self, name: Option<&str>) -> Result<Vec<&Backend>> {
+        match name {
+            Some(name) => self
+                .get(name)
+                .map(|backend| vec![backend])
+                .ok_or_else(|| anyhow!("Unknown backend \"{}\"", name)),
+            None => Ok(self.all()),
+        }
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.backends.iter().map(|b| b.name.as_str()).collect()
+    }
+
+    /// Name to show next to the backend's bots and accounts, none with a single backend
+    pub fn label<'b>(&self, backend: &'b Backend) -> Option<&'b str> {
+        (self.backends.len() > 1).then_some(backend.name.as_str())
+    }
+
+    /// `name` with the backend in front when there is more than one
+    pub fn tag(&self, backend: &Backend, name: &str) -> String {
+        match self.label(backend) {
+            Some(label) => format!("{}: {}", label, name),
+            None => name.to_string(),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use log::info;
+use rust_decimal::Decimal;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::backend_api::client::BackendAPIClient;
+use crate::config::CashFlowDetection;
+
+use super::bot_balance::BotBalance;
+use super::cash_flow::{detect_cash_flows, CashFlow};
+use super::currency_converter::{ConversionRate, CurrencyConverter};
+use super::extensions::converter::AccountStateConverter;
+use super::jsonl_cache::JsonCache;
+
+/// Totals over all accounts, in the currency of the latest balance
+pub struct BalanceSummary {
+    pub total: Decimal,
+    /// Change over the last 24 hours, leaving deposits and withdrawals out
+    pub pnl_24h: Decimal,
+    pub currency: String,
+}
+
+/// Keeps the balance history and the deposits/withdrawals that happened in between
+pub struct BalanceTracker {
+    client: Arc<BackendAPIClient>,
+    converter: CurrencyConverter,
+    detection: CashFlowDetection,
+    balances: JsonCache<BotBalance>,
+    cash_flows: JsonCache<CashFlow>,
+    /// One update at a time, so two can't record the same deposit against the same entry
+    updating: tokio::sync::Mutex<()>,
+}
+
+impl BalanceTracker {
+    pub fn new(
+        client: Arc<BackendAPIClient>,
+        converter: CurrencyConverter,
+        detection: CashFlowDetection,
+        cache_path: &Path,
+    ) -> BalanceTracker {
+        BalanceTracker {
+            client,
+            converter,
+            detection,
+            balances: JsonCache::new(cache_path.join("balance.jsonl")),
+            cash_flows: JsonCache::new(cache_path.join("cash_flows.jsonl")),
+            updating: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Stores the current account state in the reporting currency and returns the rate used
+    pub async fn update(&self, skip_unchanged: bool) -> Result<ConversionRate> {
+        let account_state = self.client.get_account_state().await?;
+        let rate = self.converter.get_rate(&account_state).await?;
+        let balance_entry = account_state.to_bot_balance(&rate);
+        let _updating = self.updating.lock().await;
+        if let Some(mut last_entry) = self.get_last_balance()? {
+            if self.detection.enabled {
+                for flow in detect_cash_flows(&last_entry, &balance_entry, &self.detection) {
+                    info!(
+                        "Detected cash flow of {} {} for {}",
+                        flow.amount, rate.to, flow.account
+                    );
+                    self.cash_flows.write(flow)?;
+                }
+            }
+            last_entry.timestamp = balance_entry.timestamp;
+            if skip_unchanged && last_entry == balance_entry {
+                return Ok(rate);
+            }
+        }
+        self.balances.write(balance_entry)?;
+        Ok(rate)
+    }
+
+    /// The current account state in the reporting currency, without storing it
+    pub async fn probe(&self) -> Result<(BotBalance, ConversionRate)> {
+        let account_state = self.client.get_account_state().await?;
+        let rate = self.converter.get_rate(&account_state).await?;
+        Ok((account_state.to_bot_balance(&rate), rate))
+    }
+
+    /// Rate of the latest cached balance, for when the backend can't be reached
+    pub fn cached_rate(&self) -> ConversionRate {
+        self.get_last_balance()
+            .ok()
+            .flatten()
+            .and_then(|last| last.conversion)
+            .unwrap_or_else(|| ConversionRate::identity(self.converter.currency()))
+    }
+
+    pub fn get_balances(&self) -> Result<Vec<BotBalance>> {
+        if self.balances.is_empty()? {
+            return Ok(vec![]);
+        }
+        self.balances.get_all_objects()
+    }
+
+    fn get_last_balance(&self) -> Result<Option<BotBalance>> {
+        if self.balances.is_empty()? {
+            return Ok(None);
+        }
+        Ok(self.balances.get_last_objects(1)?.pop())
+    }
+
+    pub fn get_cash_flows(&self) -> Result<Vec<CashFlow>> {
+        if self.cash_flows.is_empty()? {
+            return Ok(vec![]);
+        }
+        self.cash_flows.get_all_objects()
+    }
+
+    /// Summary over the accounts `visible` lets through
+    pub fn summary(&self, visible: impl Fn(&str) -> bool) -> Result<Option<BalanceSummary>> {
+        let balances = self.get_balances()?;
+        let Some(last) = balances.last() else {
+            return Ok(None);
+        };
+        let rate = self.cached_rate();
+        let total = |balance: &BotBalance| -> Decimal {
+            let factor = balance.rebase_factor(&rate);
+            balance
+                .accounts
+                .iter()
+                .filter(|(account, _)| visible(account))
+                .map(|(_, exchanges)| exchanges)
+                .flat_map(|exchanges| exchanges.values())
+                .flatten()
+                .map(|entry| entry.amount * factor)
+                .sum()
+        };
+        let day_ago = last.timestamp.saturating_sub(24 * 60 * 60);
+        let reference = balances
+            .iter()
+            .rev()
+            .find(|b| b.timestamp <= day_ago)
+            .unwrap_or(&balances[0]);
+        let flows: Decimal = self
+            .get_cash_flows()?
+            .iter()
+            .filter(|f| f.timestamp > reference.timestamp && f.timestamp <= last.timestamp)
+            .filter(|f| visible(&f.account))
+            .map(|f| f.amount * f.rebase_factor(&rate))
+            .sum();
+        Ok(Some(BalanceSummary {
+            total: total(last),
+            pnl_24h: total(last) - total(reference) - flows,
+            currency: rate.to,
+        }))
+    }
+
+    pub fn record_cash_flow(&self, flow: CashFlow) -> Result<()> {
+        self.cash_flows.write(flow)
+    }
+}
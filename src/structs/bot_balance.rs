@@ -5,10 +5,15 @@ use serde::{Deserialize, Serialize};
 
 use crate::utils::unix_timestamp::unix_timestamp;
 
+use super::currency_converter::ConversionRate;
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
 pub struct BotBalance {
     pub accounts: HashMap<String, HashMap<String, Vec<BotBalanceEntry>>>,
     pub timestamp: u64,
+    /// Rate the amounts were converted with, absent for entries stored in the backend quote
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversion: Option<ConversionRate>,
 }
 
 impl Default for BotBalance {
@@ -16,6 +21,7 @@ impl Default for BotBalance {
         Self {
             accounts: Default::default(),
             timestamp: unix_timestamp(),
+            conversion: None,
         }
     }
 }
@@ -24,22 +30,32 @@ impl Default for BotBalance {
 pub struct BotBalanceEntry {
     pub coin: String,
     pub amount: Decimal,
+    /// Number of tokens held, absent in entries cached by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units: Option<Decimal>,
 }
 
 impl BotBalance {
+    /// Factor that brings the stored amounts to the currency of `rate`
+    pub fn rebase_factor(&self, rate: &ConversionRate) -> Decimal {
+        rate.rebase_factor(self.conversion.as_ref())
+    }
+
     pub fn merge_across_exchanges(&self) -> HashMap<String, Vec<BotBalanceEntry>> {
-        let mut merged_balances: HashMap<String, HashMap<String, Decimal>> = HashMap::new();
+        let mut merged_balances: HashMap<String, HashMap<String, (Decimal, Option<Decimal>)>> =
+            HashMap::new();
 
         for (account, exchanges) in &self.accounts {
-            for (_exchange, balances) in exchanges {
+            for balances in exchanges.values() {
                 for balance in balances {
-                    let entry = merged_balances
+                    let (amount, units) = merged_balances
                         .entry(account.clone())
-                        .or_insert_with(HashMap::new)
+                        .or_default()
                         .entry(balance.coin.clone())
-                        .or_insert_with(Decimal::zero);
+                        .or_insert_with(|| (Decimal::zero(), Some(Decimal::zero())));
 
-                    *entry += balance.amount;
+                    *amount += balance.amount;
+                    *units = units.zip(balance.units).map(|(a, b)| a + b);
                 }
             }
         }
@@ -49,7 +65,11 @@ impl BotBalance {
         for (account, coins) in merged_balances {
             let entries: Vec<BotBalanceEntry> = coins
                 .into_iter()
-                .map(|(coin, amount)| BotBalanceEntry { coin, amount })
+                .map(|(coin, (amount, units))| BotBalanceEntry {
+                    coin,
+                    amount,
+                    units,
+                })
                 .collect();
 
             result.insert(account, entries);
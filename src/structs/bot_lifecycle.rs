@@ -0,0 +1,140 @@
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::backend_api::objects::{ActiveBotsResponse, Bot};
+
+/// Status and totals of a bot as last seen by the poller
+#[derive(Debug, Clone, PartialEq)]
+pub struct BotSnapshot {
+    pub status: String,
+    pub controllers: BTreeMap<String, String>,
+    pub realized_pnl_quote: Decimal,
+    pub unrealized_pnl_quote: Decimal,
+    pub global_pnl_quote: Decimal,
+    pub volume_traded: Decimal,
+}
+
+impl BotSnapshot {
+    pub fn new(bot: &Bot) -> BotSnapshot {
+        let controllers = bot.performance.values();
+        BotSnapshot {
+            status: bot.status.clone(),
+            controllers: bot
+                .performance
+                .iter()
+                .map(|(name, c)| (name.clone(), c.status.clone()))
+                .collect(),
+            realized_pnl_quote: controllers
+                .clone()
+                .map(|c| c.performance.realized_pnl_quote)
+                .sum(),
+            unrealized_pnl_quote: controllers
+                .clone()
+                .map(|c| c.performance.unrealized_pnl_quote)
+                .sum(),
+            global_pnl_quote: controllers
+                .clone()
+                .map(|c| c.performance.global_pnl_quote)
+                .sum(),
+            volume_traded: controllers.map(|c| c.performance.volume_traded).sum(),
+        }
+    }
+
+    /// Whether the bot reports it no longer trades
+    pub fn is_stopped(&self) -> bool {
+        self.status.eq_ignore_ascii_case("stopped")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LifecycleEvent {
+    Appeared {
+        bot: String,
+        snapshot: BotSnapshot,
+    },
+    /// The bot left the list of active bots, with its totals from the last poll
+    Disappeared {
+        bot: String,
+        last: BotSnapshot,
+    },
+    StatusChanged {
+        bot: String,
+        from: String,
+        to: String,
+        snapshot: BotSnapshot,
+    },
+    ControllerStatusChanged {
+        bot: String,
+        controller: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+/// Compares each poll of the active bots with the previous one
+#[derive(Default)]
+pub struct BotLifecycleTracker {
+    /// `None` until the first poll, whose bots were already running before the start
+    bots: Option<HashMap<String, BotSnapshot>>,
+}
+
+impl BotLifecycleTracker {
+    pub fn update(&mut self, response: &ActiveBotsResponse) -> Vec<LifecycleEvent> {
+        let current: HashMap<String, BotSnapshot> = response
+            .data
+            .iter()
+            .map(|(name, bot)| (name.clone(), BotSnapshot::new(bot)))
+            .collect();
+        let Some(previous) = self.bots.replace(current.clone()) else {
+            return vec![];
+        };
+        let mut events = vec![];
+        let mut names: Vec<&String> = previous.keys().chain(current.keys()).collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            let bot = name.clone();
+            match (previous.get(name), current.get(name)) {
+                (None, Some(snapshot)) => events.push(LifecycleEvent::Appeared {
+                    bot,
+                    snapshot: snapshot.clone(),
+                }),
+                (Some(last), None) => events.push(LifecycleEvent::Disappeared {
+                    bot,
+                    last: last.clone(),
+                }),
+                (Some(last), Some(snapshot)) => {
+                    if last.status != snapshot.status {
+                        events.push(LifecycleEvent::StatusChanged {
+                            bot: bot.clone(),
+                            from: last.status.clone(),
+                            to: snapshot.status.clone(),
+                            snapshot: snapshot.clone(),
+                        });
+                    }
+                    let mut controllers: Vec<&String> = last
+                        .controllers
+                        .keys()
+                        .chain(snapshot.controllers.keys())
+                        .collect();
+                    controllers.sort();
+                    controllers.dedup();
+                    for controller in controllers {
+                        let from = last.controllers.get(controller);
+                        let to = snapshot.controllers.get(controller);
+                        if from != to {
+                            events.push(LifecycleEvent::ControllerStatusChanged {
+                                bot: bot.clone(),
+                                controller: controller.clone(),
+                                from: from.cloned(),
+                                to: to.cloned(),
+                            });
+                        }
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+        events
+    }
+}
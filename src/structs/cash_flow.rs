@@ -0,0 +1,115 @@
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum::Display;
+
+use crate::config::CashFlowDetection;
+
+use super::bot_balance::BotBalance;
+use super::currency_converter::ConversionRate;
+
+#[derive(Serialize, Deserialize, Display, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum CashFlowSource {
+    Manual,
+    Detected,
+}
+
+/// A deposit (positive amount) or withdrawal (negative amount) for an account
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct CashFlow {
+    pub account: String,
+    pub amount: Decimal,
+    pub timestamp: u64,
+    pub source: CashFlowSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Rate the amount was converted with, absent when it is in the backend quote
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversion: Option<ConversionRate>,
+}
+
+impl CashFlow {
+    pub fn rebase_factor(&self, rate: &ConversionRate) -> Decimal {
+        rate.rebase_factor(self.conversion.as_ref())
+    }
+}
+
+/// Flows per account as `(timestamp, amount)` in the currency of `rate`, sorted by time
+pub fn account_flows(
+    flows: &[CashFlow],
+    rate: &ConversionRate,
+) -> HashMap<String, Vec<(u64, Decimal)>> {
+    let mut result: HashMap<String, Vec<(u64, Decimal)>> = HashMap::new();
+    for flow in flows {
+        result
+            .entry(flow.account.clone())
+            .or_default()
+            .push((flow.timestamp, flow.amount * flow.rebase_factor(rate)));
+    }
+    for flows in result.values_mut() {
+        flows.sort_by_key(|(ts, _)| *ts);
+    }
+    result
+}
+
+/// Finds changes in held units that trades can't explain.
+///
+/// A trade swaps one token for another at market price, so the units it moves are worth
+/// roughly nothing in total. Deposits and withdrawals change the units without anything
+/// flowing back, which shows up as a net value of the changed units.
+pub fn detect_cash_flows(
+    previous: &BotBalance,
+    current: &BotBalance,
+    settings: &CashFlowDetection,
+) -> Vec<CashFlow> {
+    let (Some(rate), false) = (&current.conversion, current.accounts.is_empty()) else {
+        return vec![];
+    };
+    let factor = previous.rebase_factor(rate);
+    let previous_accounts = previous.merge_across_exchanges();
+    let mut flows = vec![];
+    for (account, entries) in current.merge_across_exchanges() {
+        let Some(previous_entries) = previous_accounts.get(&account) else {
+            continue;
+        };
+        let previous_total: Decimal = previous_entries.iter().map(|e| e.amount * factor).sum();
+        let mut flow = Decimal::ZERO;
+        let mut coins: Vec<&str> = entries.iter().map(|e| e.coin.as_str()).collect();
+        coins.extend(previous_entries.iter().map(|e| e.coin.as_str()));
+        coins.sort_unstable();
+        coins.dedup();
+        for coin in coins {
+            let now = entries.iter().find(|e| e.coin == coin);
+            let before = previous_entries.iter().find(|e| e.coin == coin);
+            let units_now = now.map_or(Some(Decimal::ZERO), |e| e.units);
+            let units_before = before.map_or(Some(Decimal::ZERO), |e| e.units);
+            let (Some(units_now), Some(units_before)) = (units_now, units_before) else {
+                // Entries from before units were cached can't be compared
+                return vec![];
+            };
+            let price = [
+                now.map(|e| (e.amount, e.units)),
+                before.map(|e| (e.amount * factor, e.units)),
+            ]
+            .into_iter()
+            .flatten()
+            .find_map(|(amount, units)| units.filter(|u| !u.is_zero()).map(|u| amount / u))
+            .unwrap_or_default();
+            flow += (units_now - units_before) * price;
+        }
+        let threshold = settings
+            .min_value
+            .max(previous_total.abs() * settings.min_pct / Decimal::ONE_HUNDRED);
+        if flow.abs() >= threshold && !flow.is_zero() {
+            flows.push(CashFlow {
+                account,
+                amount: flow.round_dp(8),
+                timestamp: current.timestamp,
+                source: CashFlowSource::Detected,
+                note: None,
+                conversion: Some(rate.clone()),
+            });
+        }
+    }
+    flows
+}
@@ -0,0 +1,143 @@
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::backend_api::objects::Account;
+use crate::config::{BackendRequests, PriceSource, ReportingCurrency};
+use crate::i18n::Locale;
+
+/// Rate to go from the backend quote currency to the reporting currency
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct ConversionRate {
+    pub from: String,
+    pub to: String,
+    pub rate: Decimal,
+}
+
+impl ConversionRate {
+    pub fn identity(currency: &str) -> Self {
+        Self {
+            from: currency.to_string(),
+            to: currency.to_string(),
+            rate: Decimal::ONE,
+        }
+    }
+
+    pub fn convert(&self, value: Decimal) -> Decimal {
+        value * self.rate
+    }
+
+    /// Factor that brings an amount converted with `stored` to this rate's currency,
+    /// `None` meaning the amount is still in the backend quote currency
+    pub fn rebase_factor(&self, stored: Option<&ConversionRate>) -> Decimal {
+        match stored {
+            Some(stored) if stored.to == self.to => Decimal::ONE,
+            Some(stored) if !stored.rate.is_zero() => self.rate / stored.rate,
+            _ => self.rate,
+        }
+    }
+
+    /// Human readable rate, or `None` when no conversion happens
+    pub fn note(&self, locale: &Locale) -> Option<String> {
+        if self.from == self.to {
+            return None;
+        }
+        Some(locale.tf(
+            "rate_note",
+            &[
+                ("from", self.from.clone()),
+                ("to", self.to.clone()),
+                ("rate", locale.format_amount(self.rate, "")),
+            ],
+        ))
+    }
+}
+
+pub struct CurrencyConverter {
+    config: ReportingCurrency,
+    client: Client,
+}
+
+impl CurrencyConverter {
+    /// Price endpoints get the timeouts of backend requests, so one that hangs can't
+    /// hold up the poller
+    pub fn new(config: ReportingCurrency, requests: &BackendRequests) -> Result<CurrencyConverter> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(requests.timeout_seconds))
+            .connect_timeout(Duration::from_secs(requests.connect_timeout_seconds))
+            .build()?;
+        Ok(CurrencyConverter { config, client })
+    }
+
+    pub fn currency(&self) -> &str {
+        &self.config.currency
+    }
+
+    /// Looks up the rate for the backend quote currency, `account` is used by the backend price source
+    pub async fn get_rate(&self, account: &Account) -> Result<ConversionRate> {
+        let from = &self.config.backend_quote;
+        let to = &self.config.currency;
+        if from.eq_ignore_ascii_case(to) {
+            return Ok(ConversionRate::identity(to));
+        }
+        // Every source yields the price of one unit of the reporting currency in the backend quote
+        let price = match &self.config.price_source {
+            PriceSource::Backend => account
+                .values()
+                .flat_map(|exchanges| exchanges.values())
+                .flatten()
+                .find(|t| t.token.eq_ignore_ascii_case(to) && !t.price.is_zero())
+                .map(|t| t.price)
+                .with_context(|| format!("No price for {} in the account state", to))?,
+            PriceSource::Static { prices } => prices
+                .iter()
+                .find(|(token, _)| token.eq_ignore_ascii_case(to))
+                .map(|(_, price)| *price)
+                .with_context(|| format!("No static price configured for {}", to))?,
+            PriceSource::Http { url, price_pointer } => {
+                self.fetch_price(url, price_pointer, to, from).await?
+            }
+        };
+        if price.is_zero() {
+            return Err(anyhow!("Price for {} is zero", to));
+        }
+        Ok(ConversionRate {
+            from: from.clone(),
+            to: to.clone(),
+            rate: Decimal::ONE / price,
+        })
+    }
+
+    async fn fetch_price(
+        &self,
+        url: &str,
+        price_pointer: &str,
+        base: &str,
+        quote: &str,
+    ) -> Result<Decimal> {
+        let url = url
+            .replace("{base}", &urlencoding::encode(base))
+            .replace("{quote}", &urlencoding::encode(quote));
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<serde_json::Value>()
+            .await?;
+        let price = resp
+            .pointer(price_pointer)
+            .with_context(|| format!("No value at {} in price response", price_pointer))?;
+        match price {
+            serde_json::Value::Number(n) => {
+                let n = n.to_string();
+                Ok(Decimal::from_str(&n).or_else(|_| Decimal::from_scientific(&n))?)
+            }
+            serde_json::Value::String(s) => Ok(Decimal::from_str(s)?),
+            _ => Err(anyhow!("Price at {} is not a number", price_pointer)),
+        }
+    }
+}
@@ -6,17 +6,21 @@ use std::collections::HashMap;
 use std::str::FromStr;
 
 use crate::backend_api::objects::{Account, ActiveBotsResponse, Trade};
-use crate::structs::bot::{Bot as InternalBot, BotController, BotPNL};
+use crate::structs::bot::Bot as InternalBot;
 use crate::structs::bot_balance::{BotBalance, BotBalanceEntry};
+use crate::structs::currency_converter::ConversionRate;
 use crate::structs::trade::{Trade as InternalTrade, TradeSide};
 
 pub trait AccountStateConverter {
-    fn to_bot_balance(&self) -> BotBalance;
+    fn to_bot_balance(&self, rate: &ConversionRate) -> BotBalance;
 }
 
 impl AccountStateConverter for Account {
-    fn to_bot_balance(&self) -> BotBalance {
-        let mut result = BotBalance::default();
+    fn to_bot_balance(&self, rate: &ConversionRate) -> BotBalance {
+        let mut result = BotBalance {
+            conversion: Some(rate.clone()),
+            ..Default::default()
+        };
         for (credentials, exchange_to_tokens) in self.iter() {
             let mut coins = HashMap::new();
             for (exchange, tokens) in exchange_to_tokens.iter() {
@@ -26,7 +30,8 @@ impl AccountStateConverter for Account {
                         .iter()
                         .map(|t| BotBalanceEntry {
                             coin: t.token.clone(),
-                            amount: t.value,
+                            amount: rate.convert(t.value),
+                            units: Some(t.units),
                         })
                         .collect(),
                 );
@@ -46,6 +51,7 @@ impl<'c> TradeConverter<'c> for Trade {
         Ok(InternalTrade {
             base_asset: self.base_asset.to_owned().into(),
             quote_asset: self.quote_asset.to_owned().into(),
+            exchange: self.market.to_owned().into(),
             amount: Decimal::from_str(&self.quantity)?,
             price: Decimal::from_str(&self.price)?,
             timestamp: self.trade_timestamp,
@@ -66,35 +72,11 @@ pub trait BotsConverter<'c> {
 
 impl<'c> BotsConverter<'c> for ActiveBotsResponse {
     fn to_internal_bots(&self) -> Vec<InternalBot<'c>> {
-        return self
-            .data
-            .iter()
-            .map(|(name, bot)| {
-                let mut global_pnl = BotPNL::default();
-                let controllers = bot
-                    .performance
-                    .iter()
-                    .map(|(n, c)| {
-                        global_pnl.quote += c.performance.global_pnl_quote;
-                        global_pnl.quote += c.performance.global_pnl_quote;
-
-                        (
-                            n.clone(),
-                            BotController {
-                                pnl: BotPNL {
-                                    pct: c.performance.global_pnl_pct,
-                                    quote: c.performance.global_pnl_quote,
-                                },
-                            },
-                        )
-                    })
-                    .collect();
-                InternalBot {
-                    name: name.clone().into(),
-                    global_pnl,
-                    controllers,
-                }
+        self.data
+            .keys()
+            .map(|name| InternalBot {
+                name: name.clone().into(),
             })
-            .collect();
+            .collect()
     }
 }
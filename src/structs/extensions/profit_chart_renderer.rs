@@ -1,14 +1,19 @@
 use std::{
+    collections::HashMap,
     sync::{atomic, Arc},
     thread::JoinHandle,
     time::Duration,
 };
 
 use anyhow::Result;
+use base64::prelude::{Engine, BASE64_STANDARD};
 use headless_chrome::{protocol::cdp::Page, Browser, LaunchOptionsBuilder};
 use log::debug;
 
-use crate::structs::profit_chart;
+use crate::structs::profit_chart::{self, ChartFormat, ChartTheme};
+
+/// Space around the chart that is kept for the drop shadow, see `main.styl`
+const CHART_MARGIN: f64 = 15.0;
 
 pub trait ProfitChartRenderer {
     fn render_chart(&self) -> Result<Vec<u8>>;
@@ -46,10 +51,27 @@ impl ProfitChartRenderer for profit_chart::ChartData {
             Ok(())
         });
 
-        let launch_opts = LaunchOptionsBuilder::default().headless(true).build()?;
+        let options = &self.options;
+        let viewport_width = options.width as f64 + (CHART_MARGIN * 2.0);
+        let viewport_height = options.height as f64 + (CHART_MARGIN * 2.0);
+        let launch_opts = LaunchOptionsBuilder::default()
+            .headless(true)
+            .window_size(Some((
+                viewport_width.ceil() as u32,
+                viewport_height.ceil() as u32,
+            )))
+            // Chrome picks up the timezone from the environment, the time axis follows it
+            .process_envs(Some(HashMap::from([(
+                "TZ".to_string(),
+                self.locale.timezone.clone(),
+            )])))
+            .build()?;
         let browser = Browser::new(launch_opts)?;
         let tab = browser.new_tab()?;
-        tab.set_transparent_background_color()?;
+        // The dark card blends into Discord, the light one gets a white page around it
+        if options.theme == ChartTheme::Dark {
+            tab.set_transparent_background_color()?;
+        }
         let port = server.server_addr().to_ip().unwrap().port();
         let chart_json = serde_json::to_string(&self)?;
         let url = format!(
@@ -60,19 +82,44 @@ impl ProfitChartRenderer for profit_chart::ChartData {
         debug!("Navigating to: {}", url);
         tab.navigate_to(&url)?;
         tab.wait_until_navigated()?;
-        let chart_screenshot = tab.capture_screenshot(
-            Page::CaptureScreenshotFormatOption::Png,
-            None,
-            Some(Page::Viewport {
-                x: 0.0,
-                y: 0.0,
-                width: 450.0 + (15.0 * 2.0),
-                height: 300.0 + (15.0 * 2.0),
-                scale: 1.0,
-            }),
-            true,
-        )?;
+        let capture_format = match options.format {
+            ChartFormat::Webp => Page::CaptureScreenshotFormatOption::Webp,
+            ChartFormat::Png | ChartFormat::Svg => Page::CaptureScreenshotFormatOption::Png,
+        };
+        // Keep the base64 payload as-is, so SVG output can embed it without decoding
+        let chart_screenshot = tab
+            .call_method(Page::CaptureScreenshot {
+                format: Some(capture_format),
+                quality: None,
+                clip: Some(Page::Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: viewport_width,
+                    height: viewport_height,
+                    scale: options.device_scale_factor,
+                }),
+                from_surface: Some(true),
+                capture_beyond_viewport: None,
+            })?
+            .data;
         shall_exit.store(true, atomic::Ordering::Relaxed);
-        Ok(chart_screenshot)
+        match options.format {
+            ChartFormat::Png | ChartFormat::Webp => {
+                Ok(BASE64_STANDARD.decode(chart_screenshot)?)
+            }
+            // Chart.js draws on a canvas, so the SVG only wraps the high-DPI PNG, it
+            // isn't vector output
+            ChartFormat::Svg => Ok(format!(
+                concat!(
+                    r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+                    r#"<image width="{w}" height="{h}" href="data:image/png;base64,{data}"/>"#,
+                    "</svg>"
+                ),
+                w = viewport_width,
+                h = viewport_height,
+                data = chart_screenshot
+            )
+            .into_bytes()),
+        }
     }
 }
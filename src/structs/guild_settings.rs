@@ -0,0 +1,141 @@
+use anyhow::Result;
+use poise::serenity_prelude::{self as serenity, ChannelId, GuildId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::RwLock;
+
+use crate::utils::extract_bot_name::matches_bot_name;
+
+use super::jsonl_cache::JsonCache;
+use super::naming::Naming;
+
+/// Settings of one Discord server, anything left empty follows the config file
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GuildSettings {
+    pub trades_channel_id: Option<u64>,
+    pub announcement_channel_id: Option<u64>,
+    pub trades_enabled: Option<bool>,
+    pub announcements_enabled: Option<bool>,
+    /// Bots shown in this server, by short or full name, all of them when empty
+    pub visible_bots: Option<Vec<String>>,
+    /// Accounts shown in charts and stats of this server, all of them when empty
+    pub visible_accounts: Option<Vec<String>>,
+}
+
+impl GuildSettings {
+    pub fn shows_bot(&self, bot_name: &str) -> bool {
+        self.visible_bots
+            .as_ref()
+            .is_none_or(|visible| visible.iter().any(|name| matches_bot_name(name, bot_name)))
+    }
+
+    /// Accounts can be listed by their backend name or the name they're shown with
+    pub fn shows_account(&self, account: &str, naming: &Naming) -> bool {
+        self.visible_accounts.as_ref().is_none_or(|visible| {
+            let shown = naming.account(account);
+            visible
+                .iter()
+                .any(|name| name == account || name.eq_ignore_ascii_case(&shown))
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GuildSettingsEntry {
+    guild_id: u64,
+    settings: GuildSettings,
+}
+
+/// Where a kind of message goes in one server, with the settings that apply there
+pub struct GuildTarget {
+    pub guild_id: Option<GuildId>,
+    pub channel: ChannelId,
+    pub settings: GuildSettings,
+}
+
+/// Per-server settings, kept as a log of changes next to the balance cache
+pub struct GuildSettingsStore {
+    cache: JsonCache<GuildSettingsEntry>,
+    settings: RwLock<HashMap<u64, GuildSettings>>,
+}
+
+impl GuildSettingsStore {
+    pub fn load(cache_path: &Path) -> Result<GuildSettingsStore> {
+        let cache: JsonCache<GuildSettingsEntry> =
+            JsonCache::new(cache_path.join("guild_settings.jsonl"));
+        let mut settings = HashMap::new();
+        if !cache.is_empty()? {
+            // Later entries replace earlier ones
+            for entry in cache.get_all_objects()? {
+                settings.insert(entry.guild_id, entry.settings);
+            }
+        }
+        Ok(GuildSettingsStore {
+            cache,
+            settings: RwLock::new(settings),
+        })
+    }
+
+    pub fn get(&self, guild_id: Option<GuildId>) -> GuildSettings {
+        guild_id
+            .and_then(|id| self.settings.read().unwrap().get(&id.get()).cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn update(
+        &self,
+        guild_id: GuildId,
+        change: impl FnOnce(&mut GuildSettings),
+    ) -> Result<GuildSettings> {
+        let mut all = self.settings.write().unwrap();
+        let settings = all.entry(guild_id.get()).or_default();
+        change(settings);
+        self.cache.write(GuildSettingsEntry {
+            guild_id: guild_id.get(),
+            settings: settings.clone(),
+        })?;
+        Ok(settings.clone())
+    }
+
+    /// Channels to post to per server. `default_channel` from the config file is used
+    /// by the server it belongs to, unless that server picked another channel.
+    pub fn targets(
+        &self,
+        ctx: &serenity::Context,
+        default_channel: ChannelId,
+        default_enabled: bool,
+        channel: impl Fn(&GuildSettings) -> Option<u64>,
+        enabled: impl Fn(&GuildSettings) -> Option<bool>,
+    ) -> Vec<GuildTarget> {
+        let default_guild = ctx.cache.channel(default_channel).map(|c| c.guild_id);
+        let mut targets = vec![];
+        if default_guild.is_none() && default_enabled {
+            // Without a cache entry for it, the default channel is posted to as before
+            targets.push(GuildTarget {
+                guild_id: None,
+                channel: default_channel,
+                settings: GuildSettings::default(),
+            });
+        }
+        for guild_id in ctx.cache.guilds() {
+            let settings = self.get(Some(guild_id));
+            if !enabled(&settings).unwrap_or(default_enabled) {
+                continue;
+            }
+            let channel = match channel(&settings) {
+                Some(id) => ChannelId::new(id),
+                None if default_guild == Some(guild_id) => default_channel,
+                None => continue,
+            };
+            targets.push(GuildTarget {
+                guild_id: Some(guild_id),
+                channel,
+                settings,
+            });
+        }
+        targets
+    }
+}
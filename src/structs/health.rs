@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Recent results of one backend endpoint
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointHealth {
+    pub consecutive_failures: u32,
+    /// First failure of the current outage, kept until its recovery is reported
+    pub failing_since: Option<u64>,
+    pub last_success: Option<u64>,
+    pub last_error: Option<String>,
+    /// Whether the outage was reported, so its recovery is reported too
+    pub outage_reported: bool,
+}
+
+impl EndpointHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.consecutive_failures == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HealthEvent {
+    Outage {
+        endpoint: String,
+        since: u64,
+        failures: u32,
+        error: String,
+    },
+    Recovered {
+        endpoint: String,
+        /// Seconds between the first failure and the first success after it
+        downtime: u64,
+    },
+}
+
+/// Counts failures per backend endpoint, filled by the client on every request
+#[derive(Default)]
+pub struct HealthMonitor {
+    endpoints: Mutex<BTreeMap<String, EndpointHealth>>,
+}
+
+impl HealthMonitor {
+    pub fn record_success(&self, endpoint: &str, now: u64) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let health = endpoints.entry(endpoint.to_string()).or_default();
+        health.consecutive_failures = 0;
+        health.last_success = Some(now);
+        if !health.outage_reported {
+            health.failing_since = None;
+        }
+    }
+
+    pub fn record_failure(&self, endpoint: &str, error: String, now: u64) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let health = endpoints.entry(endpoint.to_string()).or_default();
+        health.consecutive_failures += 1;
+        health.failing_since.get_or_insert(now);
+        health.last_error = Some(error);
+    }
+
+    /// Outages that reached `failure_threshold` and recoveries since the last check
+    pub fn check(&self, failure_threshold: u32) -> Vec<HealthEvent> {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let mut events = vec![];
+        for (endpoint, health) in endpoints.iter_mut() {
+            if !health.outage_reported && health.consecutive_failures >= failure_threshold {
+                health.outage_reported = true;
+                events.push(HealthEvent::Outage {
+                    endpoint: endpoint.clone(),
+                    since: health.failing_since.unwrap_or_default(),
+                    failures: health.consecutive_failures,
+                    error: health.last_error.clone().unwrap_or_default(),
+                });
+            } else if health.outage_reported && health.is_healthy() {
+                health.outage_reported = false;
+                let since = health.failing_since.take().unwrap_or_default();
+                events.push(HealthEvent::Recovered {
+                    endpoint: endpoint.clone(),
+                    downtime: health.last_success.unwrap_or(since).saturating_sub(since),
+                });
+            }
+        }
+        events
+    }
+
+    pub fn status(&self) -> BTreeMap<String, EndpointHealth> {
+        self.endpoints.lock().unwrap().clone()
+    }
+}
@@ -1,6 +1,20 @@
+pub mod alerts;
+pub mod backends;
+pub mod balance_tracker;
 pub mod bot;
 pub mod bot_balance;
+pub mod bot_lifecycle;
+pub mod cash_flow;
+pub mod currency_converter;
 pub mod extensions;
+pub mod guild_settings;
+pub mod health;
 pub mod jsonl_cache;
+pub mod mutes;
+pub mod naming;
+pub mod performance_stats;
+pub mod privacy;
 pub mod profit_chart;
+pub mod supervisor;
 pub mod trade;
+pub mod trade_digest;
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::RwLock;
+
+use crate::utils::extract_bot_name::matches_bot_name;
+use crate::utils::unix_timestamp::unix_timestamp;
+
+use super::jsonl_cache::JsonCache;
+
+/// Trade notifications of a bot that aren't posted for a while
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Mute {
+    /// Short or full bot name
+    pub bot: String,
+    /// Unix timestamp the mute ends, muted until unmuted when empty
+    pub until: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationState {
+    pub mutes: Vec<Mute>,
+    /// Whether all trade notifications are paused
+    pub paused: bool,
+    /// Unix timestamp the pause ends, paused until resumed when empty
+    pub paused_until: Option<u64>,
+}
+
+impl NotificationState {
+    fn is_active(until: Option<u64>, now: u64) -> bool {
+        until.is_none_or(|until| until > now)
+    }
+
+    pub fn is_muted(&self, bot_name: &str, now: u64) -> bool {
+        (self.paused && Self::is_active(self.paused_until, now))
+            || self
+                .mutes
+                .iter()
+                .any(|m| Self::is_active(m.until, now) && matches_bot_name(&m.bot, bot_name))
+    }
+
+    fn without_expired(mut self, now: u64) -> Self {
+        self.mutes.retain(|m| Self::is_active(m.until, now));
+        if !Self::is_active(self.paused_until, now) {
+            self.paused = false;
+            self.paused_until = None;
+        }
+        self
+    }
+}
+
+/// Mutes and pauses, with every change stored so they survive a restart
+pub struct MuteStore {
+    cache: JsonCache<NotificationState>,
+    state: RwLock<NotificationState>,
+}
+
+impl MuteStore {
+    pub fn load(cache_path: &Path) -> Result<MuteStore> {
+        let cache: JsonCache<NotificationState> =
+            JsonCache::new(cache_path.join("notification_state.jsonl"));
+        let state = if cache.is_empty()? {
+            NotificationState::default()
+        } else {
+            cache.get_last_objects(1)?.pop().unwrap_or_default()
+        };
+        Ok(MuteStore {
+            cache,
+            state: RwLock::new(state),
+        })
+    }
+
+    pub fn is_muted(&self, bot_name: &str) -> bool {
+        self.state
+            .read()
+            .unwrap()
+            .is_muted(bot_name, unix_timestamp())
+    }
+
+    pub fn update(&self, change: impl FnOnce(&mut NotificationState)) -> Result<NotificationState> {
+        let mut state = self.state.write().unwrap();
+        let mut updated = state.clone().without_expired(unix_timestamp());
+        change(&mut updated);
+        self.cache.write(updated.clone())?;
+        *state = updated.clone();
+        Ok(updated)
+    }
+}
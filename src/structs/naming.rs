@@ -0,0 +1,92 @@
+use log::warn;
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::config::DisplayNames;
+use crate::utils::extract_bot_name::extract_bot_name;
+
+/// Names accounts, exchanges and bots are shown with, the same in every message
+#[derive(Default)]
+pub struct Naming {
+    accounts: HashMap<String, String>,
+    exchanges: HashMap<String, String>,
+    bots: HashMap<String, String>,
+    strip: Vec<Regex>,
+    shorten_bots: bool,
+}
+
+impl Naming {
+    /// Patterns that don't compile are logged and left out, `Config::validate` rejects them
+    pub fn new(names: &DisplayNames, shorten_bots: bool) -> Naming {
+        let lowercase = |map: &HashMap<String, String>| {
+            map.iter()
+                .map(|(name, alias)| (name.to_lowercase(), alias.clone()))
+                .collect()
+        };
+        Naming {
+            accounts: lowercase(&names.accounts),
+            exchanges: lowercase(&names.exchanges),
+            bots: lowercase(&names.bots),
+            strip: names
+                .strip_patterns
+                .iter()
+                .filter_map(|p| {
+                    Regex::new(p)
+                        .map_err(|e| warn!("Ignoring invalid strip pattern \"{}\": {}", p, e))
+                        .ok()
+                })
+                .collect(),
+            shorten_bots,
+        }
+    }
+
+    pub fn account(&self, name: &str) -> String {
+        self.alias(&self.accounts, name)
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    pub fn exchange(&self, name: &str) -> String {
+        self.alias(&self.exchanges, name)
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// A bot's alias, or its container name shortened to the name it was deployed with
+    pub fn bot(&self, name: &str) -> String {
+        if let Some(alias) = self.alias(&self.bots, name) {
+            return alias;
+        }
+        match extract_bot_name(name) {
+            Some(short) if self.shorten_bots => self
+                .bots
+                .get(&short.to_lowercase())
+                .cloned()
+                .unwrap_or_else(|| short.to_string()),
+            _ => name.to_string(),
+        }
+    }
+
+    /// The configured alias of `name`, or of what's left after the strip patterns
+    fn alias(&self, aliases: &HashMap<String, String>, name: &str) -> Option<String> {
+        if let Some(alias) = aliases.get(&name.to_lowercase()) {
+            return Some(alias.clone());
+        }
+        let stripped = self.strip(name);
+        if stripped.is_empty() || stripped == name {
+            return None;
+        }
+        Some(
+            aliases
+                .get(&stripped.to_lowercase())
+                .cloned()
+                .unwrap_or(stripped),
+        )
+    }
+
+    fn strip(&self, name: &str) -> String {
+        let mut name = name.to_string();
+        for pattern in self.strip.iter() {
+            name = pattern.replace_all(&name, "").into_owned();
+        }
+        name.trim().to_string()
+    }
+}
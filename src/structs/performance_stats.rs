@@ -0,0 +1,220 @@
+use rust_decimal::prelude::*;
+use std::collections::BTreeMap;
+
+use super::bot_balance::BotBalance;
+use super::cash_flow::{account_flows, CashFlow};
+use super::currency_converter::ConversionRate;
+
+const SECONDS_PER_DAY: u64 = 86400;
+/// Crypto markets never close, so annualize over every day of the year
+const TRADING_DAYS_PER_YEAR: f64 = 365.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyReturn {
+    /// Start of the day (UTC) as unix timestamp
+    pub day: u64,
+    pub ret: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerformanceStats {
+    pub start_timestamp: u64,
+    pub end_timestamp: u64,
+    pub start_value: Decimal,
+    pub end_value: Decimal,
+    /// Sum of the deposits (positive) and withdrawals (negative) in the period
+    pub net_flows: Decimal,
+    /// Profit net of flows, relative to the starting value
+    pub period_return: f64,
+    /// Return with the effect of deposits and withdrawals removed
+    pub time_weighted_return: f64,
+    /// Largest relative fall from a previous peak, as a positive fraction
+    pub max_drawdown: f64,
+    /// Annualized standard deviation of the daily returns
+    pub volatility: Option<f64>,
+    pub sharpe: Option<f64>,
+    pub sortino: Option<f64>,
+    pub best_day: Option<DailyReturn>,
+    pub worst_day: Option<DailyReturn>,
+}
+
+/// Total value per account over time, converted to the currency of `rate`
+pub fn account_series(
+    balances: &[BotBalance],
+    rate: &ConversionRate,
+) -> BTreeMap<String, Vec<(u64, Decimal)>> {
+    let mut series: BTreeMap<String, Vec<(u64, Decimal)>> = BTreeMap::new();
+    for balance in balances {
+        let factor = balance.rebase_factor(rate);
+        for (account, entries) in balance.merge_across_exchanges() {
+            let total: Decimal = entries.iter().map(|e| e.amount).sum();
+            series
+                .entry(account)
+                .or_default()
+                .push((balance.timestamp, total * factor));
+        }
+    }
+    series
+}
+
+/// Sum of the flows after `from` up to and including `to`
+fn flows_between(flows: &[(u64, Decimal)], from: u64, to: u64) -> Decimal {
+    flows
+        .iter()
+        .filter(|(ts, _)| *ts > from && *ts <= to)
+        .map(|(_, amount)| *amount)
+        .sum()
+}
+
+/// Subtracts all flows up to each point, leaving the value that was earned
+pub fn net_of_flows(series: &[(u64, Decimal)], flows: &[(u64, Decimal)]) -> Vec<(u64, Decimal)> {
+    series
+        .iter()
+        .map(|(ts, value)| (*ts, value - flows_between(flows, 0, *ts)))
+        .collect()
+}
+
+impl PerformanceStats {
+    /// Computes the statistics of one account's series, `None` when there are less than two points.
+    /// `flows` are the deposits and withdrawals of the account, their effect is left out of the returns.
+    pub fn from_series(
+        series: &[(u64, Decimal)],
+        flows: &[(u64, Decimal)],
+    ) -> Option<PerformanceStats> {
+        if series.len() < 2 {
+            return None;
+        }
+        let (start_timestamp, start_value) = series[0];
+        let (end_timestamp, end_value) = series[series.len() - 1];
+        let net_flows = flows_between(flows, start_timestamp, end_timestamp);
+
+        // Index of the time-weighted growth, each step leaves out the flows that happened in it
+        let mut index = vec![(start_timestamp, 1.0)];
+        for window in series.windows(2) {
+            let ((previous_ts, previous), (ts, value)) = (window[0], window[1]);
+            let flow = flows_between(flows, previous_ts, ts);
+            let growth = if previous.is_sign_positive() && !previous.is_zero() {
+                ((value - flow) / previous).to_f64().unwrap_or(1.0)
+            } else {
+                1.0
+            };
+            index.push((ts, index[index.len() - 1].1 * growth));
+        }
+
+        let mut peak = f64::MIN;
+        let mut max_drawdown = 0.0;
+        for (_, value) in index.iter() {
+            peak = peak.max(*value);
+            if peak > 0.0 {
+                max_drawdown = f64::max(max_drawdown, (peak - value) / peak);
+            }
+        }
+        let growth = index[index.len() - 1].1;
+        let period_return = if start_value.is_zero() {
+            0.0
+        } else {
+            ((end_value - start_value - net_flows) / start_value)
+                .to_f64()
+                .unwrap_or_default()
+        };
+
+        let daily = daily_returns(&index);
+        let returns: Vec<f64> = daily.iter().map(|d| d.ret).collect();
+        let volatility = std_dev(&returns).map(|sd| sd * TRADING_DAYS_PER_YEAR.sqrt());
+        let annual_mean = mean(&returns).map(|m| m * TRADING_DAYS_PER_YEAR);
+        let sharpe = match (annual_mean, volatility) {
+            (Some(m), Some(v)) if v > 0.0 => Some(m / v),
+            _ => None,
+        };
+        let downside = downside_deviation(&returns).map(|d| d * TRADING_DAYS_PER_YEAR.sqrt());
+        let sortino = match (annual_mean, downside) {
+            (Some(m), Some(d)) if d > 0.0 => Some(m / d),
+            _ => None,
+        };
+        let best_day = daily.iter().max_by(|a, b| a.ret.total_cmp(&b.ret)).cloned();
+        let worst_day = daily.iter().min_by(|a, b| a.ret.total_cmp(&b.ret)).cloned();
+
+        Some(PerformanceStats {
+            start_timestamp,
+            end_timestamp,
+            start_value,
+            end_value,
+            net_flows,
+            period_return,
+            time_weighted_return: growth - 1.0,
+            max_drawdown,
+            volatility,
+            sharpe,
+            sortino,
+            best_day,
+            worst_day,
+        })
+    }
+
+    /// Stats per account, limited to the last `days` when given
+    pub fn per_account(
+        balances: &[BotBalance],
+        flows: &[CashFlow],
+        rate: &ConversionRate,
+        days: Option<u32>,
+    ) -> BTreeMap<String, PerformanceStats> {
+        let flows = account_flows(flows, rate);
+        let since = match (days, balances.last()) {
+            (Some(days), Some(last)) => {
+                last.timestamp.saturating_sub(days as u64 * SECONDS_PER_DAY)
+            }
+            _ => 0,
+        };
+        account_series(balances, rate)
+            .into_iter()
+            .filter_map(|(account, series)| {
+                let series: Vec<(u64, Decimal)> =
+                    series.into_iter().filter(|(ts, _)| *ts >= since).collect();
+                let account_flows = flows.get(&account).map(Vec::as_slice).unwrap_or_default();
+                PerformanceStats::from_series(&series, account_flows).map(|stats| (account, stats))
+            })
+            .collect()
+    }
+}
+
+/// Returns between the closing values of consecutive days
+fn daily_returns(values: &[(u64, f64)]) -> Vec<DailyReturn> {
+    let mut closes: BTreeMap<u64, f64> = BTreeMap::new();
+    for (ts, value) in values {
+        closes.insert(ts - ts % SECONDS_PER_DAY, *value);
+    }
+    closes
+        .iter()
+        .zip(closes.iter().skip(1))
+        .filter(|((_, previous), _)| **previous > 0.0)
+        .map(|((_, previous), (day, value))| DailyReturn {
+            day: *day,
+            ret: value / previous - 1.0,
+        })
+        .collect()
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = mean(values)?;
+    let variance =
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    Some(variance.sqrt())
+}
+
+fn downside_deviation(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let sum: f64 = values.iter().map(|v| v.min(0.0).powi(2)).sum();
+    Some((sum / values.len() as f64).sqrt())
+}
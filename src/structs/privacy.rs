@@ -0,0 +1,69 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::{BTreeSet, HashMap};
+
+use super::currency_converter::ConversionRate;
+
+/// Value every index series starts at
+pub const INDEX_BASE: Decimal = dec!(100);
+
+/// Rescales a balance series to start at 100, so the size of the account can't be read
+/// from it. Points before the first positive balance are left out.
+pub fn index_series(series: &[(u64, Decimal)]) -> Vec<(u64, Decimal)> {
+    let points: Vec<&(u64, Decimal)> = series
+        .iter()
+        .skip_while(|(_, value)| *value <= Decimal::ZERO)
+        .collect();
+    let Some((_, start)) = points.first() else {
+        return vec![];
+    };
+    points
+        .iter()
+        .map(|(timestamp, value)| (*timestamp, (value / start * INDEX_BASE).round_dp(2)))
+        .collect()
+}
+
+/// `value` as a fraction of `total`, `None` without a positive total
+pub fn share(value: Decimal, total: Decimal) -> Option<Decimal> {
+    (total > Decimal::ZERO).then(|| value / total)
+}
+
+/// Value of a trade as a fraction of the balance `total` in the reporting currency,
+/// `None` when its quote can't be converted to that currency
+pub fn notional_share(
+    notional: Decimal,
+    quote: &str,
+    rate: &ConversionRate,
+    total: Decimal,
+) -> Option<Decimal> {
+    let value = if quote.eq_ignore_ascii_case(&rate.to) {
+        notional
+    } else if quote.eq_ignore_ascii_case(&rate.from) {
+        rate.convert(notional)
+    } else {
+        return None;
+    };
+    share(value, total)
+}
+
+/// Numbers standing in for account names, in the alphabetical order of the real names
+pub struct AccountMasks {
+    numbers: HashMap<String, usize>,
+}
+
+impl AccountMasks {
+    pub fn new(names: impl IntoIterator<Item = String>) -> AccountMasks {
+        let names: BTreeSet<String> = names.into_iter().collect();
+        AccountMasks {
+            numbers: names
+                .into_iter()
+                .enumerate()
+                .map(|(i, name)| (name, i + 1))
+                .collect(),
+        }
+    }
+
+    pub fn number(&self, name: &str) -> Option<usize> {
+        self.numbers.get(name).copied()
+    }
+}
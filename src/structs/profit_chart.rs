@@ -9,8 +9,103 @@ pub struct ChartDataEntry {
     pub balance: Decimal,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, poise::ChoiceParameter)]
+#[serde(rename_all = "camelCase")]
+pub enum ChartTheme {
+    Dark,
+    Light,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, poise::ChoiceParameter)]
+#[serde(rename_all = "camelCase")]
+pub enum ChartFormat {
+    #[name = "PNG"]
+    Png,
+    /// The PNG wrapped in an SVG file, Chart.js only draws bitmaps
+    #[name = "SVG (embedded PNG)"]
+    Svg,
+    #[name = "WebP"]
+    Webp,
+}
+
+impl ChartFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ChartFormat::Png => "png",
+            ChartFormat::Svg => "svg",
+            ChartFormat::Webp => "webp",
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("graph.{}", self.extension())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChartOptions {
+    /// Width of the chart in CSS pixels, excluding the drop shadow margin
+    pub width: u32,
+    /// Height of the chart in CSS pixels, excluding the drop shadow margin
+    pub height: u32,
+    /// Pixel density of the rendered image, use 2.0 or higher for high-DPI screens
+    pub device_scale_factor: f64,
+    pub theme: ChartTheme,
+    pub title: Option<String>,
+    /// Defaults to the reporting currency
+    pub currency_label: Option<String>,
+    pub format: ChartFormat,
+    /// Leave deposits and withdrawals out of the balance lines
+    pub net_of_flows: bool,
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        Self {
+            width: 450,
+            height: 300,
+            device_scale_factor: 1.0,
+            theme: ChartTheme::Dark,
+            title: None,
+            currency_label: None,
+            format: ChartFormat::Png,
+            net_of_flows: true,
+        }
+    }
+}
+
+/// Language, timezone and translated axis labels for the chart
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartLocale {
+    pub code: String,
+    pub timezone: String,
+    pub balance: String,
+    pub time: String,
+    /// Decimals of the balance axis, from the precision of the reporting currency
+    pub decimals: u32,
+    /// Axis labels from this size on are shortened to 1.2k, 3.4M, ...; 0 turns it off
+    pub abbreviate_from: Decimal,
+}
+
+impl Default for ChartLocale {
+    fn default() -> Self {
+        ChartLocale {
+            code: "en".to_string(),
+            timezone: "UTC".to_string(),
+            balance: "Balance".to_string(),
+            time: "Time".to_string(),
+            decimals: 2,
+            abbreviate_from: Decimal::from(1_000_000),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ChartData {
     pub chart_data: HashMap<String, Vec<ChartDataEntry>>,
+    pub options: ChartOptions,
+    pub locale: ChartLocale,
 }
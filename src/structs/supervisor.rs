@@ -0,0 +1,134 @@
+use anyhow::Result;
+use log::{error, info, warn};
+use std::any::Any;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout, Instant};
+
+/// Changes to `true` when the tasks should stop, they finish their current work first
+pub type Shutdown = watch::Receiver<bool>;
+
+/// A supervised task that stopped and is started again after `delay`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskRestart {
+    pub task: &'static str,
+    pub reason: String,
+    /// Restarts in a row, reset once the task keeps running for the maximum delay
+    pub restarts: u32,
+    pub delay: Duration,
+}
+
+/// Keeps long running tasks alive, restarting them with backoff when they panic or fail
+pub struct Supervisor {
+    shutdown: watch::Sender<bool>,
+    restarts: mpsc::UnboundedSender<TaskRestart>,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+    restart_delay: Duration,
+    max_restart_delay: Duration,
+}
+
+impl Supervisor {
+    /// The receiver gets every restart, to report them
+    pub fn new(
+        restart_delay: Duration,
+        max_restart_delay: Duration,
+    ) -> (Supervisor, mpsc::UnboundedReceiver<TaskRestart>) {
+        let (restarts, receiver) = mpsc::unbounded_channel();
+        let supervisor = Supervisor {
+            shutdown: watch::channel(false).0,
+            restarts,
+            handles: Mutex::new(Vec::new()),
+            restart_delay,
+            max_restart_delay,
+        };
+        (supervisor, receiver)
+    }
+
+    /// Runs the task made by `start` until shutdown, a task that returns before then is
+    /// restarted too
+    pub fn spawn<F, Fut>(&self, name: &'static str, mut start: F)
+    where
+        F: FnMut(Shutdown) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let mut shutdown = self.shutdown.subscribe();
+        let restarts = self.restarts.clone();
+        let (restart_delay, max_restart_delay) = (self.restart_delay, self.max_restart_delay);
+        let handle = tokio::spawn(async move {
+            let mut attempt = 0;
+            while !*shutdown.borrow() {
+                let started = Instant::now();
+                let outcome = tokio::spawn(start(shutdown.clone())).await;
+                if *shutdown.borrow() {
+                    break;
+                }
+                let reason = match outcome {
+                    Ok(Ok(())) => "stopped without an error".to_string(),
+                    Ok(Err(e)) => e.to_string(),
+                    Err(e) if e.is_panic() => {
+                        format!("panicked: {}", panic_message(e.into_panic()))
+                    }
+                    Err(e) => e.to_string(),
+                };
+                if started.elapsed() >= max_restart_delay {
+                    attempt = 0;
+                }
+                let delay = backoff(restart_delay, max_restart_delay, attempt);
+                attempt += 1;
+                error!(
+                    "Task {} {}, restarting it in {}ms",
+                    name,
+                    reason,
+                    delay.as_millis()
+                );
+                let restart = TaskRestart {
+                    task: name,
+                    reason,
+                    restarts: attempt,
+                    delay,
+                };
+                // Nobody listening only means the restart isn't reported
+                let _ = restarts.send(restart);
+                tokio::select! {
+                    _ = sleep(delay) => {}
+                    _ = shutdown.changed() => {}
+                }
+            }
+            info!("Task {} stopped", name);
+        });
+        self.handles.lock().unwrap().push(handle);
+    }
+
+    /// Tells all tasks to stop and waits up to `grace` for them
+    pub async fn shutdown(&self, grace: Duration) {
+        self.shutdown.send_replace(true);
+        let handles: Vec<_> = self.handles.lock().unwrap().drain(..).collect();
+        if timeout(grace, futures::future::join_all(handles))
+            .await
+            .is_err()
+        {
+            warn!(
+                "Not all tasks stopped within {}s, stopping anyway",
+                grace.as_secs()
+            );
+        }
+    }
+}
+
+/// The restart delay doubled per restart in a row, up to the maximum
+fn backoff(delay: Duration, max: Duration, attempt: u32) -> Duration {
+    delay.saturating_mul(2u32.saturating_pow(attempt)).min(max)
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -12,8 +12,17 @@ pub enum TradeSide {
 pub struct Trade<'c> {
     pub base_asset: Cow<'c, str>,
     pub quote_asset: Cow<'c, str>,
+    /// Connector the trade was made on, e.g. `binance`
+    pub exchange: Cow<'c, str>,
     pub amount: Decimal,
     pub price: Decimal,
     pub timestamp: u64,
     pub side: TradeSide,
 }
+
+impl<'c> Trade<'c> {
+    /// Value of the trade in the quote asset
+    pub fn notional(&self) -> Decimal {
+        self.amount * self.price
+    }
+}
@@ -0,0 +1,143 @@
+use poise::serenity_prelude::{ChannelId, GuildId};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::config::RateLimit;
+
+use super::trade::{Trade, TradeSide};
+
+/// Trades of one bot in one pair, summed up
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct DigestEntry {
+    pub base: String,
+    pub quote: String,
+    pub count: usize,
+    pub bought: Decimal,
+    pub sold: Decimal,
+    pub bought_notional: Decimal,
+    pub sold_notional: Decimal,
+}
+
+impl DigestEntry {
+    /// Change of the bot's position in the base asset
+    pub fn net_position(&self) -> Decimal {
+        self.bought - self.sold
+    }
+}
+
+/// Trades held back in a channel, by bot and pair
+#[derive(Debug)]
+pub struct TradeDigest {
+    pub guild_id: Option<GuildId>,
+    pub since: u64,
+    pub entries: BTreeMap<(String, String), DigestEntry>,
+}
+
+impl TradeDigest {
+    pub fn count(&self) -> usize {
+        self.entries.values().map(|e| e.count).sum()
+    }
+
+    fn add(&mut self, backend: Option<&str>, bot: &str, trade: &Trade<'_>) {
+        let bot = match backend {
+            Some(backend) => format!("{}: {}", backend, bot),
+            None => bot.to_string(),
+        };
+        let pair = format!("{}/{}", trade.base_asset, trade.quote_asset);
+        let entry = self
+            .entries
+            .entry((bot, pair))
+            .or_insert_with(|| DigestEntry {
+                base: trade.base_asset.to_string(),
+                quote: trade.quote_asset.to_string(),
+                ..Default::default()
+            });
+        entry.count += 1;
+        match trade.side {
+            TradeSide::Buy => {
+                entry.bought += trade.amount;
+                entry.bought_notional += trade.notional();
+            }
+            TradeSide::Sell => {
+                entry.sold += trade.amount;
+                entry.sold_notional += trade.notional();
+            }
+        }
+    }
+}
+
+/// The trades after `seen`, oldest first, so digests count every trade between polls.
+/// A bot that wasn't seen before only has its latest trade shown, not its whole history.
+pub fn new_trades<'c>(trades: Vec<Trade<'c>>, seen: Option<u64>) -> Vec<Trade<'c>> {
+    let latest = trades.iter().map(|t| t.timestamp).max().unwrap_or(0);
+    let since = seen.unwrap_or(latest.saturating_sub(1));
+    let mut trades: Vec<_> = trades.into_iter().filter(|t| t.timestamp > since).collect();
+    trades.sort_by_key(|t| t.timestamp);
+    trades
+}
+
+/// Keeps every channel within its message budget, collecting the trades over it
+#[derive(Default)]
+pub struct TradeThrottle {
+    sent: HashMap<ChannelId, VecDeque<u64>>,
+    digests: HashMap<ChannelId, TradeDigest>,
+}
+
+impl TradeThrottle {
+    /// Whether the trade can be posted now, otherwise it's added to the channel's digest.
+    /// Once a digest is started, trades keep going to it until it's posted. `backend` is
+    /// shown next to the bot when there are several, `bot` is its display name.
+    #[allow(clippy::too_many_arguments)]
+    pub fn admit(
+        &mut self,
+        settings: &RateLimit,
+        channel: ChannelId,
+        guild_id: Option<GuildId>,
+        backend: Option<&str>,
+        bot: &str,
+        trade: &Trade<'_>,
+        now: u64,
+    ) -> bool {
+        if !settings.enabled {
+            return true;
+        }
+        if !self.digests.contains_key(&channel) {
+            let sent = self.sent.entry(channel).or_default();
+            while sent
+                .front()
+                .is_some_and(|ts| *ts + settings.window_seconds <= now)
+            {
+                sent.pop_front();
+            }
+            if sent.len() < settings.max_messages {
+                sent.push_back(now);
+                return true;
+            }
+        }
+        self.digests
+            .entry(channel)
+            .or_insert_with(|| TradeDigest {
+                guild_id,
+                since: now,
+                entries: BTreeMap::new(),
+            })
+            .add(backend, bot, trade);
+        false
+    }
+
+    /// Takes the digests that collected trades for long enough
+    pub fn due_digests(&mut self, settings: &RateLimit, now: u64) -> Vec<(ChannelId, TradeDigest)> {
+        let due: Vec<ChannelId> = self
+            .digests
+            .iter()
+            .filter(|(_, d)| !settings.enabled || d.since + settings.digest_interval_seconds <= now)
+            .map(|(channel, _)| *channel)
+            .collect();
+        due.into_iter()
+            .filter_map(|channel| {
+                self.sent.entry(channel).or_default().push_back(now);
+                self.digests.remove(&channel).map(|d| (channel, d))
+            })
+            .collect()
+    }
+}
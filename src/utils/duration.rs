@@ -0,0 +1,53 @@
+use anyhow::{anyhow, Result};
+use std::time::Duration;
+
+/// Parses durations like `30m`, `2h`, `1d` or `1h30m`
+pub fn parse_duration(text: &str) -> Result<Duration> {
+    let text = text.trim();
+    let invalid = || anyhow!("Invalid duration \"{}\"", text);
+    let mut seconds = 0u64;
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = number.parse().map_err(|_| invalid())?;
+        seconds = value
+            .checked_mul(unit)
+            .and_then(|s| seconds.checked_add(s))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() || seconds == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Formats a duration like `1d 2h 5m`, leaving out seconds from a minute on
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds < 60 {
+        return format!("{}s", seconds);
+    }
+    let parts = [
+        (seconds / (24 * 60 * 60), "d"),
+        (seconds / (60 * 60) % 24, "h"),
+        (seconds / 60 % 60, "m"),
+    ];
+    parts
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
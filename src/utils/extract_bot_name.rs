@@ -1,17 +1,121 @@
-use anyhow::{Context, Result};
-use lazy_static::lazy_static;
+use anyhow::{anyhow, Result};
+use chrono::NaiveDateTime;
 use regex::Regex;
+use std::sync::OnceLock;
 
-lazy_static! {
-    static ref RE_BOT_EXTRACT: Regex =
-        Regex::new(r"hummingbot-([^_]+)-\d{4}\.\d{2}\.\d{2}_\d{2}\.\d{2}").unwrap();
+/// Container names of Hummingbot Deploy, like `hummingbot-pmm-2024.10.02_20.05`
+pub const DEFAULT_BOT_NAME_PATTERN: &str =
+    r"hummingbot-(?P<name>[^_]+)-(?P<deployed>\d{4}\.\d{2}\.\d{2}_\d{2}\.\d{2})";
+
+/// Formats of the `deployed` group, besides unix timestamps
+const DEPLOY_TIME_FORMATS: &[&str] = &[
+    "%Y.%m.%d_%H.%M",
+    "%Y-%m-%d_%H-%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y%m%d-%H%M%S",
+    "%Y%m%d%H%M%S",
+];
+
+static PARSER: OnceLock<BotNameParser> = OnceLock::new();
+
+/// What could be read from a bot's container name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedBotName<'a> {
+    /// The name it was deployed with, the full name when no pattern matched
+    pub name: &'a str,
+    pub version: Option<&'a str>,
+    /// Unix timestamp, read as UTC
+    pub deployed_at: Option<u64>,
+}
+
+/// Reads bot names with regexes that have a `name` group and optional `version` and
+/// `deployed` groups, the first one that matches wins
+pub struct BotNameParser {
+    patterns: Vec<Regex>,
+}
+
+impl BotNameParser {
+    pub fn new(patterns: &[String]) -> Result<BotNameParser> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                let regex = Regex::new(pattern)
+                    .map_err(|e| anyhow!("Invalid bot name pattern \"{}\": {}", pattern, e))?;
+                if !regex.capture_names().any(|name| name == Some("name")) {
+                    return Err(anyhow!(
+                        "Bot name pattern \"{}\" has no `(?P<name>...)` group",
+                        pattern
+                    ));
+                }
+                Ok(regex)
+            })
+            .collect::<Result<_>>()?;
+        Ok(BotNameParser { patterns })
+    }
+
+    pub fn parse<'a>(&self, bot_name: &'a str) -> Option<ParsedBotName<'a>> {
+        self.patterns.iter().find_map(|pattern| {
+            let captures = pattern.captures(bot_name)?;
+            let name = captures.name("name")?.as_str();
+            if name.is_empty() {
+                return None;
+            }
+            Some(ParsedBotName {
+                name,
+                version: captures.name("version").map(|v| v.as_str()),
+                deployed_at: captures
+                    .name("deployed")
+                    .and_then(|d| parse_deploy_time(d.as_str())),
+            })
+        })
+    }
+}
+
+impl Default for BotNameParser {
+    fn default() -> Self {
+        BotNameParser::new(&[DEFAULT_BOT_NAME_PATTERN.to_string()]).unwrap()
+    }
+}
+
+fn parse_deploy_time(text: &str) -> Option<u64> {
+    if text.len() <= 10 && text.chars().all(|c| c.is_ascii_digit()) {
+        return text.parse().ok();
+    }
+    DEPLOY_TIME_FORMATS.iter().find_map(|format| {
+        NaiveDateTime::parse_from_str(text, format)
+            .ok()
+            .and_then(|time| u64::try_from(time.and_utc().timestamp()).ok())
+    })
+}
+
+/// Sets the patterns used for all bot names, once at startup
+pub fn set_bot_name_patterns(patterns: &[String]) -> Result<()> {
+    PARSER
+        .set(BotNameParser::new(patterns)?)
+        .map_err(|_| anyhow!("Bot name patterns are already set"))
+}
+
+fn parser() -> &'static BotNameParser {
+    PARSER.get_or_init(BotNameParser::default)
+}
+
+/// The bot's parts, with its full name when it doesn't match any pattern
+pub fn parse_bot_name(bot_name: &str) -> ParsedBotName<'_> {
+    parser().parse(bot_name).unwrap_or(ParsedBotName {
+        name: bot_name,
+        version: None,
+        deployed_at: None,
+    })
+}
+
+/// The name the bot was deployed with, `None` when it doesn't match any pattern
+pub fn extract_bot_name(bot_name: &str) -> Option<&str> {
+    parser().parse(bot_name).map(|parsed| parsed.name)
 }
 
-pub fn extract_bot_name(bot_name: &str) -> Result<&str> {
-    Ok(RE_BOT_EXTRACT
-        .captures(bot_name)
-        .context("No captures found!")?
-        .get(1)
-        .context("Regex group not found!")?
-        .as_str())
+/// Whether `name` refers to the bot `bot_name`, by its short name or full container name
+pub fn matches_bot_name(name: &str, bot_name: &str) -> bool {
+    name == bot_name
+        || extract_bot_name(bot_name)
+            .is_some_and(|short_name| name.eq_ignore_ascii_case(short_name))
 }
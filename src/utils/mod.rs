@@ -1,2 +1,6 @@
+pub mod duration;
 pub mod extract_bot_name;
+pub mod number_format;
+pub mod template;
+pub mod truncate;
 pub mod unix_timestamp;
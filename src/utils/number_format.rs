@@ -0,0 +1,52 @@
+use rust_decimal::prelude::*;
+
+/// Largest number of decimals shown, to stay well within what `Decimal` can hold
+pub const MAX_DECIMALS: u32 = 18;
+
+const SUFFIXES: &[(u32, &str)] = &[(12, "T"), (9, "B"), (6, "M"), (3, "k")];
+
+/// Decimals needed to show `value` with `decimals`, or with `significant_digits` for
+/// values below one that would otherwise lose most of their digits, e.g. a price of
+/// `0.00001234` with two decimals
+pub fn display_scale(value: Decimal, decimals: u32, significant_digits: u32) -> u32 {
+    let abs = value.abs();
+    if abs.is_zero() || abs >= Decimal::ONE {
+        return decimals.min(MAX_DECIMALS);
+    }
+    let mut leading_zeros = 0;
+    let mut scaled = abs * Decimal::TEN;
+    while scaled < Decimal::ONE && leading_zeros < MAX_DECIMALS {
+        leading_zeros += 1;
+        scaled *= Decimal::TEN;
+    }
+    decimals
+        .max(leading_zeros + significant_digits)
+        .min(MAX_DECIMALS)
+}
+
+/// Rounds to `scale` decimals and drops trailing zeros past `min_decimals`
+pub fn round_trimmed(value: Decimal, scale: u32, min_decimals: u32) -> Decimal {
+    let mut rounded = value
+        .round_dp_with_strategy(scale, RoundingStrategy::MidpointAwayFromZero)
+        .normalize();
+    if rounded.scale() < min_decimals {
+        rounded.rescale(min_decimals.min(scale));
+    }
+    rounded
+}
+
+/// Splits large values into a shortened number and its suffix, e.g. `3_400_000` into
+/// `(3.4, "M")`, keeping one decimal
+pub fn abbreviate(value: Decimal) -> Option<(Decimal, &'static str)> {
+    SUFFIXES.iter().find_map(|(exponent, suffix)| {
+        let unit = Decimal::from_i128_with_scale(10i128.pow(*exponent), 0);
+        (value.abs() >= unit).then(|| {
+            (
+                (value / unit)
+                    .round_dp_with_strategy(1, RoundingStrategy::MidpointAwayFromZero)
+                    .normalize(),
+                *suffix,
+            )
+        })
+    })
+}
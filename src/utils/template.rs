@@ -0,0 +1,91 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+enum Part<'t> {
+    Text(&'t str),
+    Variable(&'t str),
+}
+
+/// Text with `{variable}` placeholders, `{{` and `}}` produce literal braces
+pub struct Template<'t> {
+    parts: Vec<Part<'t>>,
+}
+
+impl<'t> Template<'t> {
+    pub fn parse(source: &'t str) -> Result<Template<'t>> {
+        let mut parts = vec![];
+        let mut rest = source;
+        while !rest.is_empty() {
+            let Some(pos) = rest.find(['{', '}']) else {
+                parts.push(Part::Text(rest));
+                break;
+            };
+            if pos > 0 {
+                parts.push(Part::Text(&rest[..pos]));
+            }
+            rest = &rest[pos..];
+            if rest.starts_with("{{") || rest.starts_with("}}") {
+                parts.push(Part::Text(&rest[..1]));
+                rest = &rest[2..];
+            } else if rest.starts_with('}') {
+                return Err(anyhow!(
+                    "Unmatched '}}' in template \"{}\", write '}}}}' for a literal brace",
+                    source
+                ));
+            } else {
+                let end = rest.find('}').ok_or_else(|| {
+                    anyhow!(
+                        "Unclosed '{{' in template \"{}\", write '{{{{' for a literal brace",
+                        source
+                    )
+                })?;
+                let name = rest[1..end].trim();
+                if name.is_empty() || name.contains('{') {
+                    return Err(anyhow!("Invalid placeholder in template \"{}\"", source));
+                }
+                parts.push(Part::Variable(name));
+                rest = &rest[end + 1..];
+            }
+        }
+        Ok(Template { parts })
+    }
+
+    /// Parses `source` and checks it only uses the given variables
+    pub fn validate(source: &str, allowed: &[&str]) -> Result<()> {
+        let template = Template::parse(source)?;
+        for part in template.parts.iter() {
+            if let Part::Variable(name) = part {
+                if !allowed.contains(name) {
+                    return Err(anyhow!(
+                        "Unknown variable {{{}}} in template \"{}\", available: {}",
+                        name,
+                        source,
+                        allowed.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn uses(&self, variable: &str) -> bool {
+        self.parts
+            .iter()
+            .any(|part| matches!(part, Part::Variable(name) if *name == variable))
+    }
+
+    pub fn render(&self, variables: &HashMap<&str, String>) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text,
+                Part::Variable(name) => variables.get(name).map_or("", |v| v.as_str()),
+            })
+            .collect()
+    }
+}
+
+/// Parses and renders `source` in one go
+pub fn render(source: &str, variables: &HashMap<&str, String>) -> Result<String> {
+    Ok(Template::parse(source)?.render(variables))
+}
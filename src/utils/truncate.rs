@@ -0,0 +1,9 @@
+/// Shortens `text` to at most `max_chars` characters, ending in `…` when cut
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    short.push('…');
+    short
+}